@@ -0,0 +1,81 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use linkpedant::{
+    AmazonConfig, FediverseConfig, LinkReplacerConfig, MessageProcessor, ProcessingConfig,
+    ReplacerConfig, ResolverConfig, UnwrapConfig, DEFAULT_MAPPINGS,
+};
+use tokio::runtime::Runtime;
+
+const LINKS: &[&str] = &[
+    "https://www.tiktok.com/t/ZTYXjHYeg/",
+    "https://x.com/someone/status/1234567890123456789",
+    "https://www.instagram.com/p/C1a2B3c4D5e/",
+    "https://www.reddit.com/r/rust/comments/abc123/some_title/",
+    "https://www.youtube.com/watch?v=lYBUbBu4W08",
+    "https://bsky.app/profile/bsky.app/post/3ldpen4om622h",
+    "https://www.pixiv.net/en/artworks/123456789",
+    "https://example.com/not/a/supported/site",
+];
+
+fn create_processor(custom_replacers: usize) -> MessageProcessor {
+    let mut config = ReplacerConfig::new();
+    for name in DEFAULT_MAPPINGS.iter() {
+        config.insert(name.to_string(), LinkReplacerConfig::default());
+    }
+    for i in 0..custom_replacers {
+        let mut custom = LinkReplacerConfig::new(format!("fxsite{i}.com"));
+        custom
+            .set_regex(format!(r"https?://(www\.)?site{i}\.com/[^\s]+"))
+            .set_domain_re(format!(r"(www\.)?site{i}\.com"))
+            .set_strip_query(true);
+        config.insert(format!("site{i}"), custom);
+    }
+    MessageProcessor::new(
+        &config,
+        &AmazonConfig::default(),
+        &FediverseConfig::default(),
+        &UnwrapConfig::default(),
+        &ResolverConfig::default(),
+        &ProcessingConfig::default(),
+    )
+}
+
+fn create_message(links: usize) -> String {
+    LINKS
+        .iter()
+        .cycle()
+        .take(links)
+        .map(|link| format!("look at {link}"))
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+fn bench_many_links(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let processor = create_processor(0);
+    let mut group = c.benchmark_group("links_per_message");
+    for links in [1, 10, 50] {
+        let message = create_message(links);
+        group.bench_with_input(BenchmarkId::from_parameter(links), &message, |b, msg| {
+            b.iter(|| runtime.block_on(processor.process_message(msg)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_many_replacers(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let message = create_message(10);
+    let mut group = c.benchmark_group("custom_replacers");
+    for custom_replacers in [0, 50, 200] {
+        let processor = create_processor(custom_replacers);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(custom_replacers),
+            &message,
+            |b, msg| b.iter(|| runtime.block_on(processor.process_message(msg))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_many_links, bench_many_replacers);
+criterion_main!(benches);
@@ -0,0 +1,137 @@
+use crate::replace::{MessageProcessor, ReplaceResult};
+use crate::Config;
+use std::fmt::Write;
+
+/// Creates every replacer in the config and describes each problem found,
+/// nothing is returned for a valid config
+pub fn check_config(config: &Config) -> Vec<String> {
+    let processor = MessageProcessor::from_config(config);
+    let mut problems: Vec<String> = processor
+        .problems()
+        .iter()
+        .map(|(name, err)| format!("{name}: {err} ({err:?})"))
+        .collect();
+    let names: Vec<&str> = processor.replacer_names().collect();
+    let mut unknown_rules: Vec<&String> = config
+        .embed_check
+        .rules
+        .keys()
+        .filter(|name| !names.contains(&name.as_str()))
+        .collect();
+    unknown_rules.sort();
+    problems.extend(
+        unknown_rules
+            .into_iter()
+            .map(|name| format!("embed_check: rule for unknown replacer {name}")),
+    );
+    problems
+}
+
+/// Fixes the links in the input the way the bot would, followed by a line per
+/// link naming the replacer that handled it
+pub async fn fix_links(config: &Config, input: &str) -> ReplaceResult<String> {
+    let processor = MessageProcessor::from_config(config);
+    let report = processor.report_message(input).await?;
+    let mut output = report.reply().unwrap_or_else(|| input.to_owned());
+    output.push('\n');
+    for link in &report.links {
+        let replacer = link.replacer.as_deref();
+        let line = match (&link.fixed, &link.error) {
+            (_, Some(err)) => format!("{}: {err} ({})", link.original, replacer.unwrap_or("-")),
+            (Some(fixed), None) => {
+                format!("{} -> {fixed} ({})", link.original, replacer.unwrap_or("-"))
+            }
+            (None, None) => format!("{}: no replacer", link.original),
+        };
+        let _ = write!(output, "\n{line}");
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::replace::ReplacerConfig;
+    use crate::{init_tests, EmbedRule, LinkReplacerConfig};
+
+    fn create_config(replacers: ReplacerConfig) -> Config {
+        Config {
+            token: String::new(),
+            http: Default::default(),
+            amazon: Default::default(),
+            fediverse: Default::default(),
+            unwrap: Default::default(),
+            resolver: Default::default(),
+            processing: Default::default(),
+            embed_check: Default::default(),
+            reddit_media_regex: None,
+            delete_reply_reaction: Default::default(),
+            replacers,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_config() {
+        init_tests().await;
+        let mut replacers = ReplacerConfig::new();
+        replacers.insert("tiktok".into(), LinkReplacerConfig::default());
+        assert!(check_config(&create_config(replacers)).is_empty());
+
+        let mut replacers = ReplacerConfig::new();
+        replacers.insert("tiktok".into(), LinkReplacerConfig::default());
+
+        let mut missing_domain = LinkReplacerConfig::new("fixed.example.com".into());
+        missing_domain
+            .set_regex(r"https?://example\.com/[^\s]+".into())
+            .set_strip_query(true);
+        replacers.insert("missing_domain".into(), missing_domain);
+        let mut invalid_regex = LinkReplacerConfig::default();
+        invalid_regex.set_regex(r"https?://(twitter\.com/".into());
+        replacers.insert("twitter".into(), invalid_regex);
+        let mut config = create_config(replacers);
+        config
+            .embed_check
+            .rules
+            .insert("tiktok".into(), EmbedRule::Always);
+        config
+            .embed_check
+            .rules
+            .insert("instagram".into(), EmbedRule::NoMedia);
+        config.reddit_media_regex = Some(r"https?://(\w+\.)?reddit\.com/media[^\s]+".into());
+
+        let problems = check_config(&config);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[0].starts_with("missing_domain: missing config option"));
+        assert!(problems[1].starts_with("twitter: regex error"));
+        assert!(problems[2].starts_with("reddit_media_regex: invalid config option"));
+        assert_eq!(
+            problems[3],
+            "embed_check: rule for unknown replacer instagram"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fix_links() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut replacers = ReplacerConfig::new();
+        replacers.insert(
+            "tiktok".into(),
+            LinkReplacerConfig::new("d.tnktok.com".into()),
+        );
+        replacers.insert("youtube".into(), LinkReplacerConfig::new("youtu.be".into()));
+        let config = create_config(replacers);
+        let input = "https://www.tiktok.com/t/ZTYXjHYeg/ https://www.youtube.com/watch?feature=share https://example.com/page";
+
+        let output = fix_links(&config, input).await?;
+        let expected = [
+            "https://d.tnktok.com/t/ZTYXjHYeg/ https://www.youtube.com/watch?feature=share https://example.com/page",
+            "",
+            "https://www.tiktok.com/t/ZTYXjHYeg/ -> https://d.tnktok.com/t/ZTYXjHYeg/ (tiktok)",
+            "https://www.youtube.com/watch?feature=share: no query params available (youtube)",
+            "https://example.com/page: no replacer",
+        ];
+        assert_eq!(output, expected.join("\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use serde::Deserialize;
+use serenity::model::channel::Embed;
+use std::collections::HashMap;
+use url::Url;
+
+/// When a link gets fixed while the embed check is enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedRule {
+    /// Always fix the link without waiting for Discord
+    Always,
+    /// Fix the link when Discord didn't embed it at all
+    #[default]
+    Missing,
+    /// Fix the link when Discord's embed has no image or video
+    NoMedia,
+}
+
+fn embed_check_default_enabled() -> bool {
+    false
+}
+
+fn embed_check_default_delay_ms() -> u64 {
+    3000
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbedCheckConfig {
+    #[serde(default = "embed_check_default_enabled")]
+    pub enabled: bool,
+    /// How long to give Discord to embed the links of a message
+    #[serde(default = "embed_check_default_delay_ms")]
+    pub delay_ms: u64,
+    /// Rule for replacers that aren't listed in `rules`
+    #[serde(default)]
+    pub default_rule: EmbedRule,
+    /// Rules by replacer name
+    #[serde(default)]
+    pub rules: HashMap<String, EmbedRule>,
+}
+
+impl Default for EmbedCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: embed_check_default_enabled(),
+            delay_ms: embed_check_default_delay_ms(),
+            default_rule: EmbedRule::default(),
+            rules: HashMap::new(),
+        }
+    }
+}
+
+impl EmbedCheckConfig {
+    pub fn rule(&self, replacer: Option<&str>) -> EmbedRule {
+        replacer
+            .and_then(|name| self.rules.get(name))
+            .copied()
+            .unwrap_or(self.default_rule)
+    }
+}
+
+/// The parts of a Discord embed that decide whether it's usable
+#[derive(Debug, Clone, Default)]
+pub struct EmbedSummary {
+    pub url: Option<String>,
+    pub has_media: bool,
+}
+
+impl From<&Embed> for EmbedSummary {
+    fn from(embed: &Embed) -> Self {
+        let url = embed.url.clone();
+        let has_media = embed.image.is_some() || embed.video.is_some() || embed.thumbnail.is_some();
+        Self { url, has_media }
+    }
+}
+
+/// Hosts that serve the same pages under another name
+const HOST_ALIASES: &[(&str, &str)] = &[("x.com", "twitter.com")];
+
+/// Query params that pick the page rather than how it's shown
+const PAGE_QUERY_KEYS: &[&str] = &["v", "id", "p", "list", "fbid", "story_fbid"];
+
+fn normalized_host(url: &Url) -> Option<&str> {
+    let host = url.host_str()?;
+    let host = ["www.", "m.", "mobile."]
+        .iter()
+        .find_map(|prefix| host.strip_prefix(prefix))
+        .unwrap_or(host);
+    let host = HOST_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == host)
+        .map_or(host, |(_, host)| *host);
+    Some(host)
+}
+
+fn page_query(url: &Url) -> Vec<(String, String)> {
+    let mut query: Vec<_> = url
+        .query_pairs()
+        .filter(|(key, _)| PAGE_QUERY_KEYS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    query.sort();
+    query
+}
+
+/// Discord embeds the canonical link of a page, so an embed belongs to a link
+/// when both point to the same host, path and page query params, ignoring
+/// mobile subdomains and trailing slashes
+fn embed_matches(link: &str, embed_url: &str) -> bool {
+    let (Ok(link), Ok(embed_url)) = (Url::parse(link), Url::parse(embed_url)) else {
+        return false;
+    };
+    normalized_host(&link) == normalized_host(&embed_url)
+        && link.path().trim_end_matches('/') == embed_url.path().trim_end_matches('/')
+        && page_query(&link) == page_query(&embed_url)
+}
+
+/// Whether a link still needs to be fixed given the embeds Discord made
+pub fn needs_fix(link: &str, rule: EmbedRule, embeds: &[EmbedSummary]) -> bool {
+    let mut matching = embeds.iter().filter(|embed| {
+        embed
+            .url
+            .as_deref()
+            .is_some_and(|url| embed_matches(link, url))
+    });
+    match rule {
+        EmbedRule::Always => true,
+        EmbedRule::Missing => matching.next().is_none(),
+        EmbedRule::NoMedia => !matching.any(|embed| embed.has_media),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn embed(url: &str, has_media: bool) -> EmbedSummary {
+        EmbedSummary {
+            url: Some(url.into()),
+            has_media,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_needs_fix() {
+        init_tests().await;
+        let link = "https://x.com/someone/status/1234567890123456789?s=20";
+        let embeds = [embed(
+            "https://twitter.com/someone/status/1234567890123456789",
+            false,
+        )];
+
+        assert!(needs_fix(link, EmbedRule::Always, &embeds));
+        assert!(!needs_fix(link, EmbedRule::Missing, &embeds));
+        assert!(needs_fix(link, EmbedRule::NoMedia, &embeds));
+        assert!(needs_fix(link, EmbedRule::Missing, &[]));
+
+        let with_media = [embed("https://www.tiktok.com/@someone/video/1/", true)];
+        assert!(!needs_fix(
+            "https://tiktok.com/@someone/video/1",
+            EmbedRule::NoMedia,
+            &with_media
+        ));
+        assert!(needs_fix(
+            "https://www.tiktok.com/@someone/video/2",
+            EmbedRule::NoMedia,
+            &with_media
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_embed_matches() {
+        init_tests().await;
+        assert!(embed_matches(
+            "https://m.youtube.com/watch?v=lYBUbBu4W08&t=42s",
+            "https://www.youtube.com/watch?v=lYBUbBu4W08"
+        ));
+        assert!(embed_matches(
+            "https://x.com/someone/status/1",
+            "https://twitter.com/someone/status/1"
+        ));
+        assert!(!embed_matches(
+            "https://www.youtube.com/watch?v=lYBUbBu4W08",
+            "https://www.youtube.com/watch?v=xFnfOdb35FI"
+        ));
+        assert!(!embed_matches(
+            "https://www.youtube.com/watch?v=lYBUbBu4W08",
+            "https://www.youtube.com/watch"
+        ));
+        assert!(!embed_matches(
+            "https://example.com/someone/status/1",
+            "https://twitter.com/someone/status/1"
+        ));
+        assert!(!embed_matches(
+            "https://www.tiktok.com/@someone/video/1",
+            "https://www.instagram.com/@someone/video/1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rules() {
+        init_tests().await;
+        let config = EmbedCheckConfig {
+            rules: HashMap::from([("twitter".into(), EmbedRule::NoMedia)]),
+            ..Default::default()
+        };
+        assert_eq!(config.rule(Some("twitter")), EmbedRule::NoMedia);
+        assert_eq!(config.rule(Some("tiktok")), EmbedRule::Missing);
+        assert_eq!(config.rule(None), EmbedRule::Missing);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::replace::{ProcessingPool, ReplaceError, ReplaceResult};
 use crate::{
-    get_invite_command, replace::ReplaceError, BotState, DeleteReplyReaction,
-    DeleteReplyReactionConfig, LinkPedantCommands, MessageHandler,
+    get_invite_command, BotState, DeleteReplyReaction, DeleteReplyReactionConfig,
+    LinkPedantCommands, MessageProcessor, MessageReport,
 };
+use embeds::{needs_fix, EmbedSummary};
 use serenity::all::{EditMessage, ErrorResponse, Permissions, Reaction, Ready, StatusCode};
 use serenity::async_trait;
 use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage};
@@ -10,9 +12,16 @@ use serenity::model::application::{Command, Interaction};
 use serenity::model::channel::Message;
 use serenity::prelude::*;
 use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tracing::{debug, info, instrument, warn};
 
+mod embeds;
+
+pub use embeds::{EmbedCheckConfig, EmbedRule};
+
 #[derive(Debug, Copy, Clone)]
 enum NeededPermissions {
     SendMessage,
@@ -30,8 +39,6 @@ impl Display for NeededPermissions {
 
 #[derive(Debug, Error)]
 enum BotClientErrors {
-    #[error("no processors in typemap")]
-    NoProcessors,
     #[error("message not modified")]
     NotModified,
     #[error("insufficient permissions: `{0}`")]
@@ -52,20 +59,50 @@ enum BotClientErrors {
     Serenity(#[from] SerenityError),
 }
 
-pub(crate) struct Handler;
+pub(crate) struct Handler {
+    /// Shared with every message on the pool, so no lock is taken
+    processor: Arc<MessageProcessor>,
+    pool: ProcessingPool,
+    embed_check: EmbedCheckConfig,
+}
 
 impl Handler {
+    pub fn new(
+        processor: Arc<MessageProcessor>,
+        pool: ProcessingPool,
+        embed_check: EmbedCheckConfig,
+    ) -> Self {
+        Self {
+            processor,
+            pool,
+            embed_check,
+        }
+    }
+
+    /// Fixes the links of a message on the pool
+    async fn report_message(&self, content: String) -> ReplaceResult<MessageReport> {
+        self.pool
+            .report_message(self.processor.clone(), content)
+            .await
+    }
+
     async fn message_handler(&self, ctx: Context, message: Message) -> Result<(), BotClientErrors> {
         self.process_message(&ctx, message)
-            .and_then(|(ctx, original_message, reply)| async move {
-                debug!("was able to process message, replying...");
-                original_message
-                    .reply(ctx, reply)
-                    .map_err(Self::parse_errors)
-                    .await
-                    .map(|reply| (ctx, original_message, reply))
-            })
-            .and_then(|(ctx, message, reply)| async move {
+            .and_then(
+                |(ctx, original_message, reply, suppress_embeds)| async move {
+                    debug!("was able to process message, replying...");
+                    original_message
+                        .reply(ctx, reply)
+                        .map_err(Self::parse_errors)
+                        .await
+                        .map(|reply| (ctx, original_message, reply, suppress_embeds))
+                },
+            )
+            .and_then(|(ctx, message, reply, suppress_embeds)| async move {
+                if !suppress_embeds {
+                    debug!("only appended links, keeping original embeds");
+                    return Ok(());
+                }
                 let mut message = message;
                 let suppress_embeds = EditMessage::new().suppress_embeds(true);
                 if let Err(err) = message
@@ -176,20 +213,75 @@ impl Handler {
         &self,
         ctx: &'a Context,
         message: Message,
-    ) -> Result<(&'a Context, Message, String), BotClientErrors> {
-        let data_read = ctx.data.read().await;
-
-        let msg_processor_lock = data_read
-            .get::<MessageHandler>()
-            .ok_or(BotClientErrors::NoProcessors)?
-            .clone();
-
-        let processor = msg_processor_lock.read().await;
-
-        processor
-            .process_message(&message.content)?
+    ) -> Result<(&'a Context, Message, String, bool), BotClientErrors> {
+        let mut report = self.report_message(message.content.clone()).await?;
+        let mut kept_embeds = false;
+        if self.embed_check.enabled && report.reply().is_some() {
+            let embeds = message.embeds.iter().map(EmbedSummary::from).collect();
+            let refetch = || async {
+                message
+                    .channel_id
+                    .message(ctx, message.id)
+                    .await
+                    .map(|updated| updated.embeds.iter().map(EmbedSummary::from).collect())
+                    .map_err(|err| warn! {%err, "could not fetch message embeds"})
+                    .ok()
+            };
+            kept_embeds = self.check_embeds(&mut report, embeds, refetch).await;
+        }
+        for link in report.fixed() {
+            debug! {url = %link.original, replacer = ?link.replacer, "fixed link"};
+        }
+        for link in report.failed() {
+            if let Some(err) = &link.error {
+                warn! {%err, url = %link.original, replacer = ?link.replacer, "could not fix link"};
+            }
+        }
+        report
+            .reply()
             .ok_or(BotClientErrors::NotModified)
-            .map(|reply| (ctx, message, reply))
+            .map(|reply| {
+                // Hiding the embeds would also hide the ones Discord got right
+                let suppress_embeds = report.suppress_embeds() && !kept_embeds;
+                (ctx, message, reply, suppress_embeds)
+            })
+    }
+
+    /// Drops the fixes for links that Discord managed to embed on its own.
+    /// When some embeds are missing, `refetch` gets them again after giving
+    /// Discord some time. Returns whether any of Discord's embeds were kept
+    async fn check_embeds<F, Fut>(
+        &self,
+        report: &mut MessageReport,
+        mut embeds: Vec<EmbedSummary>,
+        refetch: F,
+    ) -> bool
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<Vec<EmbedSummary>>>,
+    {
+        let config = &self.embed_check;
+        let missing_embeds = report.fixed().any(|link| {
+            let rule = config.rule(link.replacer.as_deref());
+            rule != EmbedRule::Always && needs_fix(&link.target, rule, &embeds)
+        });
+        if missing_embeds {
+            debug!("waiting for discord to embed links...");
+            tokio::time::sleep(Duration::from_millis(config.delay_ms)).await;
+            if let Some(updated) = refetch().await {
+                embeds = updated;
+            }
+        }
+        let mut kept_embeds = false;
+        for link in report.links.iter_mut().filter(|link| link.fixed.is_some()) {
+            let rule = config.rule(link.replacer.as_deref());
+            if !needs_fix(&link.target, rule, &embeds) {
+                debug! {url = %link.original, "discord embedded link, not fixing"};
+                link.fixed = None;
+                kept_embeds = true;
+            }
+        }
+        kept_embeds
     }
 
     fn parse_errors(err: SerenityError) -> BotClientErrors {
@@ -311,3 +403,149 @@ impl EventHandler for Handler {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::replace::ReplacerConfig;
+    use crate::{
+        init_tests, AmazonConfig, FediverseConfig, LinkReplacerConfig, ProcessingConfig,
+        ResolverConfig, UnwrapConfig,
+    };
+    use serenity::futures::future::join_all;
+    use std::time::Instant;
+
+    const TIKTOK_LINK: &str = "https://www.tiktok.com/t/ZTYXjHYeg/";
+
+    fn create_processor(tiktok_domain: &str) -> MessageProcessor {
+        let mut config = ReplacerConfig::new();
+        config.insert(
+            "tiktok".into(),
+            LinkReplacerConfig::new(tiktok_domain.into()),
+        );
+        config.insert("twitter".into(), LinkReplacerConfig::default());
+        config.insert("youtube".into(), LinkReplacerConfig::default());
+        MessageProcessor::new(
+            &config,
+            &AmazonConfig::default(),
+            &FediverseConfig::default(),
+            &UnwrapConfig::default(),
+            &ResolverConfig::default(),
+            &ProcessingConfig::default(),
+        )
+    }
+
+    fn create_handler_with(workers: usize, embed_check: EmbedCheckConfig) -> Handler {
+        let processor = Arc::new(create_processor("d.tnktok.com"));
+        Handler::new(processor, ProcessingPool::new(workers), embed_check)
+    }
+
+    fn create_handler(workers: usize) -> Handler {
+        create_handler_with(workers, EmbedCheckConfig::default())
+    }
+
+    fn embed(url: &str) -> EmbedSummary {
+        EmbedSummary {
+            url: Some(url.into()),
+            has_media: true,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "load test, run with --ignored to measure throughput"]
+    async fn test_message_load() -> ReplaceResult<()> {
+        init_tests().await;
+        let handler = create_handler(4);
+        let messages = 2000;
+
+        let started = Instant::now();
+        let reports = join_all((0..messages).map(|i| {
+            handler.report_message(format!(
+                "clip {i}: {TIKTOK_LINK} and https://x.com/someone/status/{i}"
+            ))
+        }))
+        .await;
+        let elapsed = started.elapsed();
+        info! {
+            messages,
+            ?elapsed,
+            per_second = messages as f64 / elapsed.as_secs_f64(),
+            "replayed messages"
+        };
+        for (i, report) in reports.into_iter().enumerate() {
+            let report = report?;
+            assert_eq!(report.fixed().count(), 2);
+            assert_eq!(
+                report.reply(),
+                Some(format!(
+                    "clip {i}: https://d.tnktok.com/t/ZTYXjHYeg/ and https://fxtwitter.com/someone/status/{i}"
+                ))
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_embeds() -> ReplaceResult<()> {
+        init_tests().await;
+        let handler = create_handler_with(
+            1,
+            EmbedCheckConfig {
+                enabled: true,
+                delay_ms: 0,
+                ..Default::default()
+            },
+        );
+        let message = format!("{TIKTOK_LINK} https://x.com/someone/status/1");
+        let twitter_embed = embed("https://twitter.com/someone/status/1");
+        let tiktok_embed = embed("https://www.tiktok.com/t/ZTYXjHYeg/");
+
+        // Discord embedded the tweet but not the TikTok, even after waiting
+        let mut report = handler.report_message(message.clone()).await?;
+        let refetched = vec![twitter_embed.clone()];
+        let kept = handler
+            .check_embeds(&mut report, vec![twitter_embed.clone()], || async {
+                Some(refetched)
+            })
+            .await;
+        assert!(kept);
+        assert_eq!(
+            report.reply().as_deref(),
+            Some("https://d.tnktok.com/t/ZTYXjHYeg/ https://x.com/someone/status/1")
+        );
+
+        // Nothing embedded, every link gets fixed
+        let mut report = handler.report_message(message.clone()).await?;
+        let kept = handler
+            .check_embeds(&mut report, Vec::new(), || async { None })
+            .await;
+        assert!(!kept);
+        assert_eq!(report.fixed().count(), 2);
+
+        // Everything embedded right away, no need to look again
+        let mut report = handler.report_message(message).await?;
+        let kept = handler
+            .check_embeds(&mut report, vec![twitter_embed, tiktok_embed], || async {
+                panic!("embeds were fetched again")
+            })
+            .await;
+        assert!(kept);
+        assert!(report.reply().is_none());
+
+        // Embeds are matched against the full link, not how it was written
+        let mut report = handler
+            .report_message("x.com/someone/status/2".to_owned())
+            .await?;
+        let kept = handler
+            .check_embeds(
+                &mut report,
+                vec![embed("https://twitter.com/someone/status/2")],
+                || async { panic!("embeds were fetched again") },
+            )
+            .await;
+        assert!(kept);
+        assert!(report.reply().is_none());
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use crate::AmazonConfig;
+use crate::{AmazonConfig, EmbedCheckConfig, FediverseConfig, ResolverConfig, UnwrapConfig};
 
 pub fn get_configuration() -> Result<Config, ConfigError> {
     let config = config::Config::builder()
@@ -19,12 +19,21 @@ pub fn get_configuration() -> Result<Config, ConfigError> {
         .map(create_default_config)
 }
 
-static DEFAULT_MAPPINGS: Lazy<Vec<&'static str>> = Lazy::new(|| {
+/// Replacers that are enabled with their default settings unless configured
+pub static DEFAULT_MAPPINGS: Lazy<Vec<&'static str>> = Lazy::new(|| {
     let default_mappings = vec![
+        "bilibili",
+        "deviantart",
+        "facebook",
+        "furaffinity",
         "instagram",
+        "niconico",
         "pixiv",
         "reddit",
+        "threads",
         "tiktok",
+        "tumblr",
+        "twitch",
         "twitter",
         "youtube",
         "bsky",
@@ -69,11 +78,24 @@ impl Default for DeleteReplyReaction {
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    /// Only needed to run the bot, the subcommands work without it
+    #[serde(default)]
     pub token: String,
     #[serde(default)]
     pub http: HttpConfig,
     #[serde(default)]
     pub amazon: AmazonConfig,
+    #[serde(default)]
+    pub fediverse: FediverseConfig,
+    #[serde(default)]
+    pub unwrap: UnwrapConfig,
+    #[serde(default)]
+    pub resolver: ResolverConfig,
+    #[serde(default)]
+    pub processing: ProcessingConfig,
+    #[serde(default)]
+    pub embed_check: EmbedCheckConfig,
+    /// No longer used, reddit.com/media links are unwrapped like other wrappers
     pub reddit_media_regex: Option<String>,
     #[serde(default)]
     pub delete_reply_reaction: DeleteReplyReaction,
@@ -86,6 +108,9 @@ pub struct LinkReplacerConfig {
     pub regex: Option<String>,
     pub domain_re: Option<String>,
     pub strip_query: Option<bool>,
+    /// Replacers with a higher priority are tried first, ties are broken by name
+    #[serde(default)]
+    pub priority: i32,
     #[serde(flatten)]
     pub custom_config: HashMap<String, String>,
 }
@@ -97,6 +122,7 @@ impl LinkReplacerConfig {
             regex: None,
             domain_re: None,
             strip_query: None,
+            priority: 0,
             custom_config: HashMap::new(),
         }
     }
@@ -115,6 +141,11 @@ impl LinkReplacerConfig {
         self.strip_query = Some(strip_query);
         self
     }
+
+    pub fn set_priority(&mut self, priority: i32) -> &mut Self {
+        self.priority = priority;
+        self
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -130,3 +161,35 @@ impl Default for HttpConfig {
         Self { host, port }
     }
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ProcessingConfig {
+    /// How long fixing the links of a single message may take
+    pub message_timeout_ms: u64,
+    /// Whether links typed without `https://` are fixed
+    pub fix_bare_links: bool,
+    /// Backtracking steps a custom replacer regex may take on a single link
+    pub regex_backtrack_limit: usize,
+    /// How long matching the links of a single message may take
+    pub regex_budget_ms: u64,
+    /// How many messages are processed at once
+    pub workers: usize,
+}
+
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        let message_timeout_ms = 5000;
+        let fix_bare_links = true;
+        let regex_backtrack_limit = 100_000;
+        let regex_budget_ms = 250;
+        let workers = std::thread::available_parallelism().map_or(4, usize::from);
+        Self {
+            message_timeout_ms,
+            fix_bare_links,
+            regex_backtrack_limit,
+            regex_budget_ms,
+            workers,
+        }
+    }
+}
@@ -24,7 +24,7 @@ mod test {
         let client = reqwest::Client::new();
 
         let response = client
-            .get(&format!("{}/health", &app.address))
+            .get(format!("{}/health", &app.address))
             .send()
             .await
             .expect("failed to execute request");
@@ -38,7 +38,7 @@ mod test {
         }
 
         let response = client
-            .get(&format!("{}/health", &app.address))
+            .get(format!("{}/health", &app.address))
             .send()
             .await
             .expect("failed to execute request");
@@ -6,14 +6,19 @@ use std::sync::Arc;
 use thiserror::Error;
 use tracing::{error, info, warn};
 
+pub use cli::{check_config, fix_links};
 pub(crate) use client::Handler;
+pub use client::{EmbedCheckConfig, EmbedRule};
 pub(crate) use commands::{get_invite_command, LinkPedantCommands};
 pub use config::{
-    get_configuration, Config, DeleteReplyReaction, HttpConfig, LinkReplacerConfig, ReplacerConfig,
+    get_configuration, Config, DeleteReplyReaction, HttpConfig, LinkReplacerConfig,
+    ProcessingConfig, ReplacerConfig, DEFAULT_MAPPINGS,
 };
-pub use replace::AmazonConfig;
-pub(crate) use replace::MessageProcessor;
+use replace::ProcessingPool;
+pub use replace::{AmazonConfig, FediverseConfig, ResolverConfig, UnwrapConfig};
+pub use replace::{LinkReport, MessageProcessor, MessageReport};
 
+mod cli;
 mod client;
 mod commands;
 mod config;
@@ -24,7 +29,7 @@ mod util;
 rust_i18n::i18n!("locales", fallback = "en");
 
 #[cfg(test)]
-pub(crate) use util::{init_tests, spawn_test_server};
+pub(crate) use util::{init_tests, spawn_redirect_server, spawn_test_server};
 
 pub use util::{get_subscriber, init_subscriber};
 
@@ -32,12 +37,6 @@ pub type Result<T> = ::core::result::Result<T, LinkPedantError>;
 
 pub use http::{start_server, AtomicBotStatus, BotStatus, HttpError};
 
-pub(crate) struct MessageHandler;
-
-impl TypeMapKey for MessageHandler {
-    type Value = Arc<RwLock<MessageProcessor>>;
-}
-
 pub(crate) struct BotState;
 
 impl TypeMapKey for BotState {
@@ -68,15 +67,23 @@ pub struct LinkPedant {
 
 impl LinkPedant {
     pub async fn new(config: Config) -> Result<Self> {
+        if config.token.is_empty() {
+            let err = ::config::ConfigError::NotFound("token".into());
+            error! {%err, "a token is needed to run the bot"};
+            return Err(err.into());
+        }
         let intents = GatewayIntents::MESSAGE_CONTENT
             | GatewayIntents::DIRECT_MESSAGES
             | GatewayIntents::GUILD_MESSAGES
             | GatewayIntents::GUILDS
             | GatewayIntents::GUILD_MESSAGE_REACTIONS;
         let state = Data::new(AtomicBotStatus::new(BotStatus::Starting));
+        let processor = Arc::new(MessageProcessor::from_config(&config));
         let http_config = config.http;
+        let pool = ProcessingPool::new(config.processing.workers);
+        let handler = Handler::new(processor, pool, config.embed_check);
         let client = Client::builder(&config.token, intents)
-            .event_handler(Handler)
+            .event_handler(handler)
             .await
             .map_err(|err| {
                 error! {%err, "could not create client"}
@@ -84,11 +91,6 @@ impl LinkPedant {
             })?;
         {
             let mut data = client.data.write().await;
-            data.insert::<MessageHandler>(Arc::new(RwLock::new(MessageProcessor::new(
-                &config.replacers,
-                config.reddit_media_regex.to_owned(),
-                &config.amazon,
-            ))));
             data.insert::<DeleteReplyReactionConfig>(Arc::new(RwLock::new(
                 config.delete_reply_reaction.clone(),
             )));
@@ -1,8 +1,57 @@
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: linkpedant [check-config | fix <url|->]";
+
 #[tokio::main]
-async fn main() -> linkpedant::Result<()> {
-    let subscriber = linkpedant::get_subscriber("info".into());
+async fn main() -> linkpedant::Result<ExitCode> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // The subcommands only print what they find, without logging every replacer
+    let log_level = if args.is_empty() { "info" } else { "warn" };
+    let subscriber = linkpedant::get_subscriber(log_level.into());
     linkpedant::init_subscriber(subscriber);
     let config = linkpedant::get_configuration()?;
-    let mut linkpedant = linkpedant::LinkPedant::new(config).await?;
-    linkpedant.run().await
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let mut linkpedant = linkpedant::LinkPedant::new(config).await?;
+            linkpedant.run().await?;
+            Ok(ExitCode::SUCCESS)
+        }
+        ["check-config"] => {
+            let problems = linkpedant::check_config(&config);
+            for problem in &problems {
+                eprintln!("{problem}");
+            }
+            if problems.is_empty() {
+                println!("config is valid");
+                Ok(ExitCode::SUCCESS)
+            } else {
+                Ok(ExitCode::FAILURE)
+            }
+        }
+        ["fix", input] => {
+            let mut input = input.to_owned();
+            if input == "-" {
+                input.clear();
+                if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+                    eprintln!("could not read stdin: {err}");
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+            match linkpedant::fix_links(&config, input.trim_end()).await {
+                Ok(output) => {
+                    println!("{output}");
+                    Ok(ExitCode::SUCCESS)
+                }
+                Err(err) => {
+                    eprintln!("could not fix links: {err}");
+                    Ok(ExitCode::FAILURE)
+                }
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            Ok(ExitCode::from(2))
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use super::{LinkReplacer, ReplaceConfigResult, ReplaceError, ReplaceResult};
+use super::{
+    build_regex, BacktrackLimit, LinkReplacer, ReplaceConfigResult, ReplaceError, ReplaceResult,
+};
 use fancy_regex::Regex;
 use serde::Deserialize;
 use tracing::{debug, instrument, warn};
@@ -63,6 +65,13 @@ impl AmazonReplacer {
     }
 }
 
+impl BacktrackLimit for AmazonReplacer {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
 impl LinkReplacer for AmazonReplacer {
     fn get_regex(&self) -> &Regex {
         &self.regex
@@ -118,7 +127,7 @@ mod test {
         let url = "https://www.amazon.com/Gears-Wonderland-steampunk-fantasy-Anderson-ebook/dp/B005USJ5U8/ref=sr_1_1?ie=UTF8&qid=1491136398&sr=8-1&keywords=gears+of+wonderland";
         let expected = "https://www.amazon.com/dp/B005USJ5U8/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -130,7 +139,7 @@ mod test {
         let url = "https://www.amazon.com/Gears-Wonderland-steampunk-fantasy-Anderson-ebook/dp/B005USJ5U8/ref=sr_1_1?ie=UTF8&qid=1491136398&sr=8-1&keywords=gears+of+wonderland";
         let expected = "https://amzn.com/dp/B005USJ5U8/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -1,5 +1,8 @@
+use super::pattern::{build_regex, BacktrackLimit};
 use core::fmt::Debug;
-use fancy_regex::{Captures, Regex};
+use fancy_regex::Regex;
+use futures::future::{self, BoxFuture};
+use std::ops::Range;
 use thiserror::Error;
 use tracing::{debug, info, instrument, warn};
 use url::Url;
@@ -26,6 +29,15 @@ pub enum ReplaceError {
 
     #[error("config error")]
     Config(#[from] ReplaceConfigError),
+
+    #[error("link was not fixed before the deadline")]
+    Timeout,
+
+    #[error("regex budget of the message was exceeded")]
+    RegexBudget(String),
+
+    #[error("message processing task failed")]
+    Worker,
 }
 
 #[derive(Debug, Error, Clone)]
@@ -33,11 +45,30 @@ pub enum ReplaceConfigError {
     #[error("regex error")]
     Regex(#[from] fancy_regex::Error),
 
+    #[error("regex may backtrack catastrophically")]
+    UnsafeRegex(String),
+
     #[error("missing config option")]
     MissingOption(String),
 
     #[error("invalid custom replacer")]
     InvalidReplacer(String),
+
+    #[error("invalid config option")]
+    InvalidOption(String),
+
+    #[error("could not read file")]
+    Io(String),
+}
+
+/// How a fixed link ends up in the reply
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplaceMode {
+    /// The fixed link takes the place of the original one
+    #[default]
+    Replace,
+    /// The original link stays and the fixed link is added after the message
+    Append,
 }
 
 pub type ReplaceResult<T> = std::result::Result<T, ReplaceError>;
@@ -49,6 +80,7 @@ pub struct ProcessorConfig {
     pub link_regex: Regex,
     pub domain_regex: Regex,
     pub strip_query: bool,
+    pub kept_query: Vec<String>,
 }
 
 impl ProcessorConfig {
@@ -65,8 +97,23 @@ impl ProcessorConfig {
             link_regex,
             domain_regex,
             strip_query,
+            kept_query: Vec::new(),
         })
     }
+
+    /// Query params that survive `strip_query`
+    pub fn keep_query_params(mut self, params: &[&str]) -> Self {
+        self.kept_query = params.iter().map(|p| p.to_string()).collect();
+        self
+    }
+}
+
+impl BacktrackLimit for ProcessorConfig {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.link_regex = build_regex(self.link_regex.as_str(), limit)?;
+        self.domain_regex = build_regex(self.domain_regex.as_str(), limit)?;
+        Ok(self)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -98,7 +145,15 @@ impl LinkReplacer for LinkProcessor {
         debug! {%new_host, "setting new host"};
         url.set_host(Some(&new_host))?;
         if self.config.strip_query {
+            let kept: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(field, _)| self.config.kept_query.iter().any(|k| k == field))
+                .map(|(field, value)| (field.into_owned(), value.into_owned()))
+                .collect();
             url.set_query(None);
+            if !kept.is_empty() {
+                url.query_pairs_mut().extend_pairs(kept);
+            }
         }
         let new_url = url.to_string();
         debug! {%new_url, "new url"};
@@ -109,41 +164,98 @@ impl LinkReplacer for LinkProcessor {
 pub trait LinkReplacer: Debug {
     fn get_regex(&self) -> &Regex;
 
-    fn is_match(&self, url: &str) -> ReplaceResult<bool> {
+    fn mode(&self) -> ReplaceMode {
+        ReplaceMode::Replace
+    }
+
+    /// Where the link this replacer fixes is in the url, if anywhere
+    fn find_match(&self, url: &str) -> ReplaceResult<Option<Range<usize>>> {
         self.get_regex()
-            .is_match(url)
+            .find(url)
+            .map(|found| found.map(|found| found.range()))
             .map_err(|err| ReplaceError::Config(err.into()))
     }
 
+    fn is_match(&self, url: &str) -> ReplaceResult<bool> {
+        Ok(self.find_match(url)?.is_some())
+    }
+
+    /// Fixes the link found by [`Self::find_match`] without running the regex again
     #[instrument(skip(self))]
-    fn process_url(&self, url: &str) -> ReplaceResult<String> {
-        let re = self.get_regex();
-        let mut process_error = None;
+    fn process_match(&self, url: &str, range: Range<usize>) -> ReplaceResult<String> {
         debug!("Processing URL...");
-        let new_url = re
-            .replace(url, |caps: &Captures<'_>| {
-                let orig_url = &caps[0];
-                self.transform_url(orig_url)
-                    .and_then(|processed_url| {
-                        if processed_url.eq(orig_url) {
-                            let err = ReplaceError::UrlNotModified(processed_url);
-                            process_error = Some(err.clone());
-                            Err(err)
-                        } else {
-                            info! {%orig_url, %processed_url, "replaced url"};
-                            Ok(processed_url)
-                        }
-                    })
-                    .map_err(|err| warn! {%err, "could not transform url"})
-                    .unwrap_or(orig_url.to_string())
-            })
-            .to_string();
-        if let Some(err) = process_error {
-            Err(err)
-        } else {
-            Ok(new_url)
+        let orig_url = &url[range.clone()];
+        match self.transform_url(orig_url) {
+            Ok(processed_url) if processed_url.eq(orig_url) => {
+                let err = ReplaceError::UrlNotModified(processed_url);
+                warn! {%err, "could not transform url"};
+                Err(err)
+            }
+            Ok(processed_url) => {
+                info! {%orig_url, %processed_url, "replaced url"};
+                Ok(format!(
+                    "{}{processed_url}{}",
+                    &url[..range.start],
+                    &url[range.end..]
+                ))
+            }
+            Err(err) => {
+                warn! {%err, "could not transform url"};
+                Err(err)
+            }
         }
     }
 
     fn transform_url(&self, url: &str) -> ReplaceResult<String>;
 }
+
+/// Replacers that can do I/O while fixing a link. Every [`LinkReplacer`] is one
+pub trait AsyncLinkReplacer: Debug + Send + Sync {
+    fn mode(&self) -> ReplaceMode {
+        ReplaceMode::Replace
+    }
+
+    fn find_match(&self, url: &str) -> ReplaceResult<Option<Range<usize>>>;
+
+    fn is_match(&self, url: &str) -> ReplaceResult<bool> {
+        Ok(self.find_match(url)?.is_some())
+    }
+
+    fn process_match<'a>(
+        &'a self,
+        url: &'a str,
+        range: Range<usize>,
+    ) -> BoxFuture<'a, ReplaceResult<String>>;
+
+    /// Fixes the link right away, or returns `None` if that means waiting
+    /// for I/O and [`Self::process_match`] has to be awaited instead
+    fn process_match_now(&self, _url: &str, _range: Range<usize>) -> Option<ReplaceResult<String>> {
+        None
+    }
+}
+
+impl<T: LinkReplacer + Send + Sync> AsyncLinkReplacer for T {
+    fn mode(&self) -> ReplaceMode {
+        LinkReplacer::mode(self)
+    }
+
+    fn find_match(&self, url: &str) -> ReplaceResult<Option<Range<usize>>> {
+        LinkReplacer::find_match(self, url)
+    }
+
+    fn is_match(&self, url: &str) -> ReplaceResult<bool> {
+        LinkReplacer::is_match(self, url)
+    }
+
+    fn process_match<'a>(
+        &'a self,
+        url: &'a str,
+        range: Range<usize>,
+    ) -> BoxFuture<'a, ReplaceResult<String>> {
+        Box::pin(future::ready(LinkReplacer::process_match(self, url, range)))
+    }
+
+    fn process_match_now(&self, url: &str, range: Range<usize>) -> Option<ReplaceResult<String>> {
+        Some(LinkReplacer::process_match(self, url, range))
+    }
+}
@@ -0,0 +1,163 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+
+#[derive(Debug, Clone)]
+pub struct BilibiliReplacer {
+    inner: LinkProcessor,
+}
+
+const BILIBILI_NEW_DOMAIN: &str = "vxbilibili.com";
+const BILIBILI_LINK_RE_STR: &str =
+    r"https?://(((www|m)\.)?bilibili\.com/video/(BV\w{10}|av\d+)|b23\.tv/\w+)[^\s]*";
+const BILIBILI_DOMAIN_RE_STR: &str = r"((www|m)\.)?bilibili\.com|b23\.tv";
+/// The video part and timestamp, tracking params like spm_id_from and vd_source are dropped
+const BILIBILI_KEPT_QUERY: &[&str] = &["p", "t"];
+
+pub fn bilibili_default_new_domain() -> String {
+    BILIBILI_NEW_DOMAIN.to_owned()
+}
+
+pub fn bilibili_default_link_re_str() -> &'static str {
+    BILIBILI_LINK_RE_STR
+}
+
+pub fn bilibili_default_domain_re_str() -> &'static str {
+    BILIBILI_DOMAIN_RE_STR
+}
+
+pub fn bilibili_default_strip_query() -> bool {
+    true
+}
+
+impl BilibiliReplacer {
+    pub fn new(config: BilibiliConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for BilibiliReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Bilibili URL...");
+        self.inner.transform_url(url)
+    }
+}
+
+pub struct BilibiliConfig {
+    inner: ProcessorConfig,
+}
+
+impl BilibiliConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?
+            .keep_query_params(BILIBILI_KEPT_QUERY);
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<BilibiliConfig> for ProcessorConfig {
+    fn from(value: BilibiliConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for BilibiliConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for BilibiliConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(bilibili_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(bilibili_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(bilibili_default_domain_re_str()),
+            value.strip_query.unwrap_or(bilibili_default_strip_query()),
+        )
+    }
+}
+
+impl Default for BilibiliConfig {
+    fn default() -> Self {
+        Self::new(
+            bilibili_default_new_domain(),
+            bilibili_default_link_re_str(),
+            bilibili_default_domain_re_str(),
+            bilibili_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for BilibiliConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> BilibiliReplacer {
+        BilibiliReplacer::new(BilibiliConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.bilibili.com/video/BV1GJ411x7h7/?spm_id_from=333.788.recommend_more_video.1&vd_source=0123456789abcdef";
+        let expected = "https://vxbilibili.com/video/BV1GJ411x7h7/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.bilibili.com/video/BV1GJ411x7h7/?p=3&t=95&spm_id_from=333.1007&vd_source=0123456789abcdef";
+        let expected = "https://vxbilibili.com/video/BV1GJ411x7h7/?p=3&t=95";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://m.bilibili.com/video/av170001?t=12.5";
+        let expected = "https://vxbilibili.com/video/av170001?t=12.5";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://b23.tv/a1B2c3D";
+        let expected = "https://vxbilibili.com/a1B2c3D";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+}
@@ -1,20 +1,25 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceError, ReplaceResult,
 };
 use fancy_regex::Regex;
+use once_cell::sync::Lazy;
 use tracing::{debug, instrument};
 
 #[derive(Debug, Clone)]
 pub struct BskyReplacer {
     inner: LinkProcessor,
+    new_domain: String,
 }
 
 const BSKY_NEW_DOMAIN: &str = "bskyx.app";
-const BSKY_LINK_RE_STR: &str =
-    r"https?://bsky\.app/profile/((\w|\.|-)+|(did:plc:[234567a-z]{24}))/post/[234567a-z]{13}(?!/)";
+const BSKY_LINK_RE_STR: &str = r"(https?://bsky\.app/profile/((\w|\.|-)+|(did:plc:[234567a-z]{24}))/(post|feed|lists)/[\w-]+|at://((\w|\.|-)+|(did:plc:[234567a-z]{24}))/app\.bsky\.(feed\.post|feed\.generator|graph\.list)/[\w-]+)/?(?![\w/-])";
 const BSKY_DOMAIN_RE_STR: &str = r"bsky\.app";
 
+static BSKY_AT_URI_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^at://(?<authority>[^/\s]+)/(?<collection>[\w.]+)/(?<rkey>[\w-]+)").unwrap()
+});
+
 pub fn bsky_default_new_domain() -> String {
     BSKY_NEW_DOMAIN.to_owned()
 }
@@ -33,8 +38,34 @@ pub fn bsky_default_strip_query() -> bool {
 
 impl BskyReplacer {
     pub fn new(config: BskyConfig) -> Self {
+        let new_domain = config.inner.new_domain.clone();
         let inner = LinkProcessor::new(config.into());
-        Self { inner }
+        Self { inner, new_domain }
+    }
+
+    /// Turns an `at://` record URI into the matching web URL on the new domain
+    fn transform_at_uri(&self, uri: &str) -> ReplaceResult<String> {
+        let caps = BSKY_AT_URI_RE
+            .captures(uri)
+            .map_err(|err| ReplaceError::Config(err.into()))?
+            .ok_or(ReplaceError::MissingGroup("authority".to_string()))?;
+        let group = |name: &str| {
+            caps.name(name)
+                .map(|m| m.as_str())
+                .ok_or(ReplaceError::MissingGroup(name.to_string()))
+        };
+        let authority = group("authority")?;
+        let rkey = group("rkey")?;
+        let page = match group("collection")? {
+            "app.bsky.feed.post" => "post",
+            "app.bsky.feed.generator" => "feed",
+            "app.bsky.graph.list" => "lists",
+            _ => return Err(ReplaceError::UrlNotModified(uri.to_string())),
+        };
+        Ok(format!(
+            "https://{new_domain}/profile/{authority}/{page}/{rkey}",
+            new_domain = self.new_domain
+        ))
     }
 }
 
@@ -45,8 +76,12 @@ impl LinkReplacer for BskyReplacer {
 
     #[instrument(skip(self))]
     fn transform_url(&self, url: &str) -> ReplaceResult<String> {
-        debug!("Transforming Instagram URL...");
-        self.inner.transform_url(url)
+        debug!("Transforming Bluesky URL...");
+        if url.starts_with("at://") {
+            self.transform_at_uri(url)
+        } else {
+            self.inner.transform_url(url)
+        }
     }
 }
 
@@ -72,6 +107,13 @@ impl From<BskyConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for BskyConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for BskyConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -119,8 +161,79 @@ mod test {
         let url = "https://bsky.app/profile/albertflasher.bsky.social/post/3ldpen4om622h";
         let expected = "https://bskyx.app/profile/albertflasher.bsky.social/post/3ldpen4om622h";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_transform_trailing_slash() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://bsky.app/profile/albertflasher.bsky.social/post/3ldpen4om622h/";
+        let expected = "https://bskyx.app/profile/albertflasher.bsky.social/post/3ldpen4om622h/";
+
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_feeds_and_lists() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://bsky.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/feed/whats-hot",
+                "https://bskyx.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/feed/whats-hot",
+            ),
+            (
+                "https://bsky.app/profile/bsky.app/lists/3kflf2r3lwg2x",
+                "https://bskyx.app/profile/bsky.app/lists/3kflf2r3lwg2x",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_at_uris() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/3ldpen4om622h",
+                "https://bskyx.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/post/3ldpen4om622h",
+            ),
+            (
+                "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.generator/whats-hot",
+                "https://bskyx.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/feed/whats-hot",
+            ),
+            (
+                "at://bsky.app/app.bsky.graph.list/3kflf2r3lwg2x",
+                "https://bskyx.app/profile/bsky.app/lists/3kflf2r3lwg2x",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignore_post_sub_pages() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://bsky.app/profile/albertflasher.bsky.social/post/3ldpen4om622h/liked-by";
+
+        assert!(!test_replacer.is_match(url)?);
+        Ok(())
+    }
 }
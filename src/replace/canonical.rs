@@ -0,0 +1,137 @@
+use std::borrow::Cow;
+use tracing::debug;
+use url::Url;
+
+/// Sites whose `www.` and mobile subdomains serve the same pages as the
+/// bare domain
+const FOLDED_DOMAINS: &[&str] = &[
+    "bilibili.com",
+    "bsky.app",
+    "deviantart.com",
+    "facebook.com",
+    "furaffinity.net",
+    "instagram.com",
+    "nicovideo.jp",
+    "pixiv.net",
+    "reddit.com",
+    "threads.com",
+    "threads.net",
+    "tiktok.com",
+    "tumblr.com",
+    "twitch.tv",
+    "twitter.com",
+    "x.com",
+    "youtube.com",
+];
+
+const FOLDED_SUBDOMAINS: &[&str] = &["www", "m", "mobile", "mbasic", "web", "sp", "touch"];
+
+/// Sites that also serve their pages on locale subdomains such as `de.` or `pt-br.`
+const LOCALE_DOMAINS: &[&str] = &["facebook.com", "reddit.com"];
+
+fn is_locale(subdomain: &str) -> bool {
+    let mut parts = subdomain.split('-');
+    let is_code = |part: Option<&str>| {
+        part.is_some_and(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_lowercase()))
+    };
+    is_code(parts.next()) && parts.next().is_none_or(|region| is_code(Some(region)))
+}
+
+/// The host without the subdomains that don't change which page it serves
+fn fold_host(host: &str) -> Option<&str> {
+    let (subdomain, domain) = host.split_once('.')?;
+    let folded = (FOLDED_DOMAINS.contains(&domain) && FOLDED_SUBDOMAINS.contains(&subdomain))
+        || (LOCALE_DOMAINS.contains(&domain) && is_locale(subdomain));
+    folded.then_some(domain)
+}
+
+/// Rewrites a link into the single form replacers expect: a lowercase
+/// punycode host without `www.`, mobile or locale subdomains, no empty
+/// fragment and at most one trailing slash. Links that can't be parsed are
+/// left as they are
+pub fn canonicalize(link: &str) -> Cow<'_, str> {
+    let Ok(mut url) = Url::parse(link) else {
+        return Cow::Borrowed(link);
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return Cow::Borrowed(link);
+    }
+    // Parsing already lowercases the host and converts it to punycode
+    if let Some(domain) = url.host_str().and_then(fold_host).map(str::to_owned) {
+        if url.set_host(Some(&domain)).is_err() {
+            return Cow::Borrowed(link);
+        }
+    }
+    if url.fragment() == Some("") {
+        url.set_fragment(None);
+    }
+    let path = url.path();
+    let trimmed = path.trim_end_matches('/');
+    if path.len() > trimmed.len() + 1 {
+        let path = format!("{trimmed}/");
+        url.set_path(&path);
+    }
+    if url.as_str() == link {
+        return Cow::Borrowed(link);
+    }
+    debug! {%link, canonical = %url, "canonicalized link"};
+    Cow::Owned(url.into())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    #[tokio::test]
+    async fn test_canonicalize() {
+        init_tests().await;
+        let cases = [
+            (
+                "https://mobile.twitter.com/user/status/1",
+                "https://twitter.com/user/status/1",
+            ),
+            (
+                "https://WWW.TikTok.com/t/ZTYXjHYeg/",
+                "https://tiktok.com/t/ZTYXjHYeg/",
+            ),
+            (
+                "https://m.youtube.com/watch?v=lYBUbBu4W08#",
+                "https://youtube.com/watch?v=lYBUbBu4W08",
+            ),
+            (
+                "https://de-de.facebook.com/reel/123",
+                "https://facebook.com/reel/123",
+            ),
+            (
+                "https://de.reddit.com/r/rust/comments/abc/title//",
+                "https://reddit.com/r/rust/comments/abc/title/",
+            ),
+            (
+                "https://bücher.example/page",
+                "https://xn--bcher-kva.example/page",
+            ),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(canonicalize(link), expected, "{link}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keep_meaningful_parts() {
+        init_tests().await;
+        let cases = [
+            "https://music.youtube.com/watch?v=lYBUbBu4W08",
+            "https://old.reddit.com/r/rust/",
+            "https://vm.tiktok.com/ZMhvqKJ4B/",
+            "https://www.example.com/page#section",
+            "https://someone.tumblr.com/post/123",
+            "at://did:plc:abc/app.bsky.feed.post/123",
+            "not a link",
+        ];
+        for link in cases {
+            assert_eq!(canonicalize(link), link);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/// One or more links per supported site, used to find replacers that claim
+/// the same link at startup
+pub(super) const SAMPLE_URLS: &[&str] = &[
+    "https://www.amazon.com/Some-Product-Name/dp/B0C1234567/ref=sr_1_1?keywords=product",
+    "https://www.bilibili.com/video/BV1GJ411x7h7/?spm_id_from=333.999",
+    "https://b23.tv/AbCdEf1",
+    "https://bsky.app/profile/bsky.app/post/3ldpen4om622h",
+    "https://www.deviantart.com/someartist/art/Some-Artwork-1234567890",
+    "https://www.facebook.com/reel/1234567890123456",
+    "https://fb.watch/AbCdEf123/",
+    "https://mastodon.social/@Gargron/113749447366429385",
+    "https://www.furaffinity.net/view/12345678/",
+    "https://www.instagram.com/p/C1a2B3c4D5e/",
+    "https://www.instagram.com/reel/C1a2B3c4D5e/",
+    "https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT",
+    "https://music.youtube.com/watch?v=lYBUbBu4W08",
+    "https://www.nicovideo.jp/watch/sm9",
+    "https://www.pixiv.net/en/artworks/123456789",
+    "https://www.reddit.com/r/rust/comments/abc123/some_title/",
+    "https://www.reddit.com/r/rust/s/AbCdEf123",
+    "https://www.reddit.com/media?url=https%3A%2F%2Fi.redd.it%2Fabc123.jpeg",
+    "https://www.threads.net/@zuck/post/C1a2B3c4D5e",
+    "https://www.tiktok.com/t/ZTYXjHYeg/",
+    "https://www.tiktok.com/@someone/video/7412345678901234567",
+    "https://vm.tiktok.com/ZMhvqKJ4B/",
+    "https://staff.tumblr.com/post/752395784163934208/new-features",
+    "https://clips.twitch.tv/SomeClipName-AbCdEf123",
+    "https://x.com/someone/status/1234567890123456789",
+    "https://twitter.com/someone/status/1234567890123456789",
+    "https://www.youtube.com/watch?v=lYBUbBu4W08",
+    "https://youtube.com/shorts/xFnfOdb35FI",
+];
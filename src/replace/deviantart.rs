@@ -0,0 +1,172 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct DeviantArtReplacer {
+    inner: LinkProcessor,
+}
+
+const DEVIANTART_NEW_DOMAIN: &str = "fixdeviantart.com";
+const DEVIANTART_LINK_RE_STR: &str = r"https?://((www\.)?deviantart\.com/([\w-]+/art|view)/[\w-]+|(?!www\.)[\w-]+\.deviantart\.com/art/[\w-]+)[^\s]*";
+const DEVIANTART_DOMAIN_RE_STR: &str = r"([\w-]+\.)?deviantart\.com";
+
+pub fn deviantart_default_new_domain() -> String {
+    DEVIANTART_NEW_DOMAIN.to_owned()
+}
+
+pub fn deviantart_default_link_re_str() -> &'static str {
+    DEVIANTART_LINK_RE_STR
+}
+
+pub fn deviantart_default_domain_re_str() -> &'static str {
+    DEVIANTART_DOMAIN_RE_STR
+}
+
+pub fn deviantart_default_strip_query() -> bool {
+    true
+}
+
+impl DeviantArtReplacer {
+    pub fn new(config: DeviantArtConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for DeviantArtReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming DeviantArt URL...");
+        let mut url = Url::parse(url)?;
+        // Legacy <user>.deviantart.com/art/<slug> links keep the user in the path
+        if let Some(user) = url
+            .host_str()
+            .and_then(|host| host.strip_suffix(".deviantart.com"))
+            .filter(|user| *user != "www")
+            .map(str::to_owned)
+        {
+            let path = format!("/{user}{}", url.path());
+            url.set_path(&path);
+        }
+        url.set_fragment(None);
+        self.inner.transform_url(url.as_str())
+    }
+}
+
+pub struct DeviantArtConfig {
+    inner: ProcessorConfig,
+}
+
+impl DeviantArtConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?;
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<DeviantArtConfig> for ProcessorConfig {
+    fn from(value: DeviantArtConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for DeviantArtConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for DeviantArtConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(deviantart_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(deviantart_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(deviantart_default_domain_re_str()),
+            value
+                .strip_query
+                .unwrap_or(deviantart_default_strip_query()),
+        )
+    }
+}
+
+impl Default for DeviantArtConfig {
+    fn default() -> Self {
+        Self::new(
+            deviantart_default_new_domain(),
+            deviantart_default_link_re_str(),
+            deviantart_default_domain_re_str(),
+            deviantart_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for DeviantArtConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> DeviantArtReplacer {
+        DeviantArtReplacer::new(DeviantArtConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.deviantart.com/some-artist/art/Sunset-Over-The-Bay-1012345678";
+        let expected = "https://fixdeviantart.com/some-artist/art/Sunset-Over-The-Bay-1012345678";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        // Links from before the move to deviantart.com/<user> keep the artist in the subdomain
+        let url = "https://some-artist.deviantart.com/art/Sunset-Over-The-Bay-1012345678";
+        let expected = "https://fixdeviantart.com/some-artist/art/Sunset-Over-The-Bay-1012345678";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.deviantart.com/view/1012345678";
+        let expected = "https://fixdeviantart.com/view/1012345678";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        assert!(!test_replacer.is_match("https://www.deviantart.com/some-artist")?);
+        assert!(!test_replacer.is_match("https://www.deviantart.com/some-artist/gallery")?);
+        Ok(())
+    }
+}
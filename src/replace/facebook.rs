@@ -0,0 +1,196 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+
+#[derive(Debug, Clone)]
+pub struct FacebookReplacer {
+    inner: LinkProcessor,
+}
+
+const FACEBOOK_NEW_DOMAIN: &str = "facebed.com";
+const FACEBOOK_LINK_RE_STR: &str = r"https?://(((www|m|web|mbasic)\.)?facebook\.com/(reel/\d+|watch/?\?([^\s]*&)?v=\d+|share/[rvp]/\w+|[\w.-]+/(posts|videos)/[\w.-]+|(permalink|story|photo)\.php\?[^\s]+|photo/?\?[^\s]+)|fb\.watch/[\w-]+)[^\s]*";
+const FACEBOOK_DOMAIN_RE_STR: &str = r"((www|m|web|mbasic)\.)?facebook\.com|fb\.watch";
+/// Query params that identify the post or video, everything else is tracking
+const FACEBOOK_KEPT_QUERY: &[&str] = &["v", "story_fbid", "id", "fbid"];
+
+pub fn facebook_default_new_domain() -> String {
+    FACEBOOK_NEW_DOMAIN.to_owned()
+}
+
+pub fn facebook_default_link_re_str() -> &'static str {
+    FACEBOOK_LINK_RE_STR
+}
+
+pub fn facebook_default_domain_re_str() -> &'static str {
+    FACEBOOK_DOMAIN_RE_STR
+}
+
+pub fn facebook_default_strip_query() -> bool {
+    true
+}
+
+impl FacebookReplacer {
+    pub fn new(config: FacebookConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for FacebookReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Facebook URL...");
+        self.inner.transform_url(url)
+    }
+}
+
+pub struct FacebookConfig {
+    inner: ProcessorConfig,
+}
+
+impl FacebookConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?
+            .keep_query_params(FACEBOOK_KEPT_QUERY);
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<FacebookConfig> for ProcessorConfig {
+    fn from(value: FacebookConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for FacebookConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for FacebookConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(facebook_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(facebook_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(facebook_default_domain_re_str()),
+            value.strip_query.unwrap_or(facebook_default_strip_query()),
+        )
+    }
+}
+
+impl Default for FacebookConfig {
+    fn default() -> Self {
+        Self::new(
+            facebook_default_new_domain(),
+            facebook_default_link_re_str(),
+            facebook_default_domain_re_str(),
+            facebook_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for FacebookConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> FacebookReplacer {
+        FacebookReplacer::new(FacebookConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.facebook.com/reel/1234567890123456?fs=e&s=TIeQ9V&mibextid=wwXIfr";
+        let expected = "https://facebed.com/reel/1234567890123456";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.facebook.com/watch/?v=1234567890123456&ref=sharing";
+        let expected = "https://facebed.com/watch/?v=1234567890123456";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://m.facebook.com/watch?ref=sharing&v=1234567890123456";
+        let expected = "https://facebed.com/watch?v=1234567890123456";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.facebook.com/share/r/1AbCdEfGhJ/?mibextid=wwXIfr";
+        let expected = "https://facebed.com/share/r/1AbCdEfGhJ/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.facebook.com/share/v/1AbCdEfGhJ/";
+        let expected = "https://facebed.com/share/v/1AbCdEfGhJ/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://fb.watch/tGx3bXdS9w/";
+        let expected = "https://facebed.com/tGx3bXdS9w/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.facebook.com/some.page/posts/pfbid02AbCdEfGh?__cft__[0]=AZXx&__tn__=%2CO%2CP-R";
+        let expected = "https://facebed.com/some.page/posts/pfbid02AbCdEfGh";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.facebook.com/permalink.php?story_fbid=pfbid0AbCdEf&id=100064000000000&mibextid=Nif5oz";
+        let expected =
+            "https://facebed.com/permalink.php?story_fbid=pfbid0AbCdEf&id=100064000000000";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://web.facebook.com/photo.php?fbid=10160000000000000&set=a.10150000&type=3";
+        let expected = "https://facebed.com/photo.php?fbid=10160000000000000";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        assert!(!test_replacer.is_match("https://www.facebook.com/some.page")?);
+        assert!(!test_replacer.is_match("https://www.facebook.com/groups/123456789")?);
+        Ok(())
+    }
+}
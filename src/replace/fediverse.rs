@@ -0,0 +1,237 @@
+use super::{
+    build_regex, BacktrackLimit, LinkReplacer, ReplaceConfigError, ReplaceConfigResult,
+    ReplaceError, ReplaceResult,
+};
+use fancy_regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+use tracing::{debug, info, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct FediverseReplacer {
+    new_domain: String,
+    regex: Regex,
+    instances: HashSet<String>,
+}
+
+const FEDIVERSE_LINK_RE_STR: &str =
+    r"https?://[\w.-]+\.[a-zA-Z]{2,}/(@[\w.-]+(@[\w.-]+)?/\d+\b|notes/\w+)[^\s]*";
+const FEDIVERSE_NEW_DOMAIN: &str = "fxmastodon.net";
+
+fn fediverse_default_re_str() -> String {
+    FEDIVERSE_LINK_RE_STR.to_string()
+}
+
+fn fediverse_default_new_domain() -> String {
+    FEDIVERSE_NEW_DOMAIN.to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FediverseConfig {
+    #[serde(default = "fediverse_default_re_str")]
+    pub regex: String,
+    #[serde(default = "fediverse_default_new_domain")]
+    pub new_domain: String,
+    #[serde(default)]
+    pub instances: Vec<String>,
+    /// File with one instance host per line, `#` starts a comment
+    pub instances_file: Option<String>,
+}
+
+impl Default for FediverseConfig {
+    fn default() -> Self {
+        let regex = fediverse_default_re_str();
+        let new_domain = fediverse_default_new_domain();
+        Self {
+            regex,
+            new_domain,
+            instances: Vec::new(),
+            instances_file: None,
+        }
+    }
+}
+
+impl FediverseConfig {
+    fn load_instances(&self) -> ReplaceConfigResult<HashSet<String>> {
+        let mut instances: HashSet<String> = self
+            .instances
+            .iter()
+            .map(|host| host.trim().to_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+        if let Some(path) = self.instances_file.as_deref() {
+            info!("Loading fediverse instances from {}...", path);
+            let contents = fs::read_to_string(path)
+                .map_err(|err| ReplaceConfigError::Io(format!("{path}: {err}")))?;
+            instances.extend(
+                contents
+                    .lines()
+                    .map(|line| line.split('#').next().unwrap_or_default())
+                    .map(|host| host.trim().to_lowercase())
+                    .filter(|host| !host.is_empty()),
+            );
+        }
+        Ok(instances)
+    }
+}
+
+impl FediverseReplacer {
+    pub fn new(config: &FediverseConfig) -> ReplaceConfigResult<Self> {
+        let new_domain = config.new_domain.clone();
+        let regex = Regex::new(&config.regex)?;
+        let instances = config.load_instances()?;
+        Ok(Self {
+            new_domain,
+            regex,
+            instances,
+        })
+    }
+
+    pub fn has_instances(&self) -> bool {
+        !self.instances.is_empty()
+    }
+
+    pub fn instances(&self) -> impl Iterator<Item = &str> {
+        self.instances.iter().map(String::as_str)
+    }
+
+    fn is_instance(&self, url: &str) -> bool {
+        Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| self.instances.contains(host)))
+            .unwrap_or(false)
+    }
+}
+
+impl BacktrackLimit for FediverseReplacer {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
+impl LinkReplacer for FediverseReplacer {
+    fn get_regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    fn find_match(&self, url: &str) -> ReplaceResult<Option<Range<usize>>> {
+        if !self.is_instance(url) {
+            return Ok(None);
+        }
+        self.regex
+            .find(url)
+            .map(|found| found.map(|found| found.range()))
+            .map_err(|err| ReplaceError::Config(err.into()))
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Fediverse URL...");
+        let url = Url::parse(url)?;
+        let instance = url.host_str().ok_or(ReplaceError::UrlHost)?;
+        // The fixer takes the instance as the first path segment
+        let new_url = format!(
+            "https://{new_domain}/{instance}{path}",
+            new_domain = self.new_domain,
+            path = url.path()
+        );
+        debug! {%new_url, "new url"};
+        Ok(new_url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+    use std::path::PathBuf;
+
+    fn create_test_replacer() -> ReplaceConfigResult<FediverseReplacer> {
+        FediverseReplacer::new(&FediverseConfig {
+            instances: vec!["mastodon.social".into(), "Misskey.io".into()],
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer()?;
+        let url = "https://mastodon.social/@Gargron/113749447366429385?utm_source=share";
+        let expected = "https://fxmastodon.net/mastodon.social/@Gargron/113749447366429385";
+
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_misskey_notes() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer()?;
+        let url = "https://misskey.io/notes/9xk2ab3cd4";
+        let expected = "https://fxmastodon.net/misskey.io/notes/9xk2ab3cd4";
+
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignore_unknown_instances() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer()?;
+        assert!(!test_replacer.is_match("https://example.com/@someone/113749447366429385")?);
+        assert!(!test_replacer.is_match("https://mastodon.social/@Gargron")?);
+        // Profile tabs aren't posts, status ids are always numeric
+        for tab in ["with_replies", "media", "followers", "following"] {
+            let url = format!("https://mastodon.social/@Gargron/{tab}");
+            assert!(!test_replacer.is_match(&url)?, "{url}");
+        }
+        assert!(!test_replacer.is_match("https://mastodon.social/@Gargron@mastodon.social/media")?);
+        Ok(())
+    }
+
+    /// Removes the file when the test ends, even if it fails
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_instances_file() -> ReplaceResult<()> {
+        init_tests().await;
+        let file = TempFile(std::env::temp_dir().join(format!(
+            "linkpedant-fediverse-instances-{}.txt",
+            std::process::id()
+        )));
+        fs::write(
+            &file.0,
+            "# instances\nhachyderm.io\n\n  tech.lgbt # comment\n",
+        )
+        .unwrap();
+        let test_replacer = FediverseReplacer::new(&FediverseConfig {
+            instances_file: Some(file.0.to_string_lossy().to_string()),
+            ..Default::default()
+        })?;
+        assert!(test_replacer.is_match("https://hachyderm.io/@someone/113749447366429385")?);
+        assert!(test_replacer.is_match("https://tech.lgbt/@someone/113749447366429385")?);
+
+        let missing = FediverseReplacer::new(&FediverseConfig {
+            instances_file: Some("/does/not/exist.txt".into()),
+            ..Default::default()
+        });
+        assert!(missing.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct FurAffinityReplacer {
+    inner: LinkProcessor,
+}
+
+const FURAFFINITY_NEW_DOMAIN: &str = "fxraffinity.net";
+const FURAFFINITY_LINK_RE_STR: &str =
+    r"https?://((www|sfw)\.)?furaffinity\.net/(view|full)/\d+[^\s]*";
+const FURAFFINITY_DOMAIN_RE_STR: &str = r"((www|sfw)\.)?furaffinity\.net";
+
+pub fn furaffinity_default_new_domain() -> String {
+    FURAFFINITY_NEW_DOMAIN.to_owned()
+}
+
+pub fn furaffinity_default_link_re_str() -> &'static str {
+    FURAFFINITY_LINK_RE_STR
+}
+
+pub fn furaffinity_default_domain_re_str() -> &'static str {
+    FURAFFINITY_DOMAIN_RE_STR
+}
+
+pub fn furaffinity_default_strip_query() -> bool {
+    true
+}
+
+impl FurAffinityReplacer {
+    pub fn new(config: FurAffinityConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for FurAffinityReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming FurAffinity URL...");
+        let mut url = Url::parse(url)?;
+        // /full/<id> is the same submission as /view/<id>
+        if let Some(rest) = url.path().strip_prefix("/full/").map(str::to_owned) {
+            url.set_path(&format!("/view/{rest}"));
+        }
+        self.inner.transform_url(url.as_str())
+    }
+}
+
+pub struct FurAffinityConfig {
+    inner: ProcessorConfig,
+}
+
+impl FurAffinityConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?;
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<FurAffinityConfig> for ProcessorConfig {
+    fn from(value: FurAffinityConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for FurAffinityConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for FurAffinityConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(furaffinity_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(furaffinity_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(furaffinity_default_domain_re_str()),
+            value
+                .strip_query
+                .unwrap_or(furaffinity_default_strip_query()),
+        )
+    }
+}
+
+impl Default for FurAffinityConfig {
+    fn default() -> Self {
+        Self::new(
+            furaffinity_default_new_domain(),
+            furaffinity_default_link_re_str(),
+            furaffinity_default_domain_re_str(),
+            furaffinity_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for FurAffinityConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> FurAffinityReplacer {
+        FurAffinityReplacer::new(FurAffinityConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.furaffinity.net/view/54123456/";
+        let expected = "https://fxraffinity.net/view/54123456/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        // The full size view is the same submission
+        let url = "https://www.furaffinity.net/full/54123456/";
+        let expected = "https://fxraffinity.net/view/54123456/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://furaffinity.net/view/54123456/?upload-successful";
+        let expected = "https://fxraffinity.net/view/54123456/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://sfw.furaffinity.net/view/54123456/";
+        let expected = "https://fxraffinity.net/view/54123456/";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        assert!(!test_replacer.is_match("https://www.furaffinity.net/user/someartist/")?);
+        assert!(!test_replacer.is_match("https://www.furaffinity.net/gallery/someartist/")?);
+        Ok(())
+    }
+}
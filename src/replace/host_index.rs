@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+/// Narrows down which replacers are worth trying on a link using its host
+#[derive(Debug, Default)]
+pub struct HostIndex {
+    /// Replacer positions by the domain they match, subdomains included
+    domains: HashMap<String, Vec<usize>>,
+    /// Replacers that may match a link on any host
+    unindexed: Vec<usize>,
+    len: usize,
+}
+
+impl HostIndex {
+    /// Indexes replacers by position, `None` means a replacer can't be narrowed
+    /// down to a set of domains
+    pub fn new<'a>(replacer_hosts: impl Iterator<Item = Option<&'a [String]>>) -> Self {
+        let mut index = Self::default();
+        for (position, hosts) in replacer_hosts.enumerate() {
+            match hosts {
+                Some(hosts) => {
+                    for host in hosts {
+                        index
+                            .domains
+                            .entry(host.to_lowercase())
+                            .or_default()
+                            .push(position);
+                    }
+                }
+                None => index.unindexed.push(position),
+            }
+            index.len += 1;
+        }
+        index
+    }
+
+    /// Positions of the replacers that may match a link on this host, in the
+    /// order they were indexed. Every replacer is a candidate when the host
+    /// isn't known
+    pub fn candidates(&self, host: Option<&str>) -> Vec<usize> {
+        let Some(mut domain) = host else {
+            return (0..self.len).collect();
+        };
+        let mut candidates = self.unindexed.clone();
+        loop {
+            if let Some(positions) = self.domains.get(domain) {
+                candidates.extend(positions);
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => break,
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// The domain a link regex is limited to, for simple regexes such as
+/// `https?://(www\.)?some-site\.com/[^\s]+`. Subdomains are included
+pub fn regex_domain(link_regex: &str) -> Option<String> {
+    let rest = ["https?://", "https://", "http://"]
+        .iter()
+        .find_map(|scheme| link_regex.strip_prefix(scheme))?;
+    // An optional subdomain group such as `(www\.)?` or `(\w+\.)?`
+    let rest = match rest.strip_prefix('(') {
+        Some(group) => {
+            let (subdomain, rest) = group.split_once(")?")?;
+            if subdomain.contains(['|', '(']) || !subdomain.ends_with(r"\.") {
+                return None;
+            }
+            rest
+        }
+        None => rest,
+    };
+    let mut domain = String::new();
+    let mut chars = rest.chars();
+    // The domain has to be followed by a path for the host to end there
+    loop {
+        match chars.next()? {
+            '/' => break,
+            '\\' if chars.next()? == '.' => domain.push('.'),
+            c if c.is_ascii_alphanumeric() || c == '-' => domain.push(c.to_ascii_lowercase()),
+            _ => return None,
+        }
+    }
+    domain.contains('.').then_some(domain)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn hosts(hosts: &[&str]) -> Vec<String> {
+        hosts.iter().map(|host| host.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_candidates() {
+        init_tests().await;
+        let tiktok = hosts(&["tiktok.com"]);
+        let twitter = hosts(&["x.com", "twitter.com"]);
+        let music = hosts(&["music.youtube.com", "open.spotify.com"]);
+        let index = HostIndex::new(
+            [
+                Some(&tiktok[..]),
+                None,
+                Some(&twitter[..]),
+                Some(&music[..]),
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(index.candidates(Some("www.tiktok.com")), [0, 1]);
+        assert_eq!(index.candidates(Some("twitter.com")), [1, 2]);
+        assert_eq!(index.candidates(Some("music.youtube.com")), [1, 3]);
+        assert_eq!(index.candidates(Some("www.youtube.com")), [1]);
+        assert_eq!(index.candidates(Some("fx.com")), [1]);
+        assert_eq!(index.candidates(None), [0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_regex_domain() {
+        init_tests().await;
+        let cases = [
+            (r"https?://(www\.)?site0\.com/[^\s]+", Some("site0.com")),
+            (
+                r"https?://(\w+\.)?some-site\.com/[^\s]+",
+                Some("some-site.com"),
+            ),
+            (r"https://Example\.org/p/\d+", Some("example.org")),
+            (r"https?://(x|twitter)\.com/(\w){1,15}/status/[^\s]+", None),
+            (r"https?://(www|m\.)?site\.com/[^\s]+", None),
+            (r"https?://site\.com", None),
+            (r"https?://site\.co.uk/[^\s]+", None),
+            (r"[^\s]+\.site\.com/[^\s]+", None),
+        ];
+        for (regex, expected) in cases {
+            assert_eq!(regex_domain(regex).as_deref(), expected, "{regex}");
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
 };
 use fancy_regex::Regex;
+use strum::EnumString;
 use tracing::{debug, instrument};
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub struct InstagramReplacer {
@@ -11,8 +13,40 @@ pub struct InstagramReplacer {
 }
 
 const INSTAGRAM_NEW_DOMAIN: &str = "ddinstagram.com";
-const INSTAGRAM_LINK_RE_STR: &str = r"https?://(\w+\.)?instagram.com/(p|reel|stories)/[^\s]+";
+const INSTAGRAM_LINK_RE_STR: &str =
+    r"https?://(\w+\.)?instagram\.com/([\w.]+/)?(p|reels?|stories|share|tv)/[^\s]+";
 const INSTAGRAM_DOMAIN_RE_STR: &str = r"(\w+\.)?(instagram\.com)";
+const INSTAGRAM_FRONT_END_OPTION: &str = "front_end";
+/// Selects the image of a carousel post
+const INSTAGRAM_KEPT_QUERY: &[&str] = &["img_index"];
+/// Post types that can be prefixed by the profile name
+const INSTAGRAM_POST_TYPES: &[&str] = &["p", "reel", "reels", "tv"];
+const INSTAGRAM_TOP_LEVEL_PATHS: &[&str] = &["p", "reel", "reels", "share", "stories", "tv"];
+
+/// InstaFix compatible front-ends that can be picked with the `front_end` option
+#[derive(Debug, EnumString, PartialEq)]
+enum InstagramFrontEnd {
+    #[strum(ascii_case_insensitive)]
+    DdInstagram,
+    #[strum(ascii_case_insensitive)]
+    KkInstagram,
+    #[strum(ascii_case_insensitive)]
+    InstagramEz,
+    #[strum(ascii_case_insensitive)]
+    UuInstagram,
+}
+
+impl InstagramFrontEnd {
+    fn domain(&self) -> String {
+        match self {
+            Self::DdInstagram => "ddinstagram.com",
+            Self::KkInstagram => "kkinstagram.com",
+            Self::InstagramEz => "instagramez.com",
+            Self::UuInstagram => "uuinstagram.com",
+        }
+        .to_owned()
+    }
+}
 
 pub fn instagram_default_new_domain() -> String {
     INSTAGRAM_NEW_DOMAIN.to_owned()
@@ -45,7 +79,32 @@ impl LinkReplacer for InstagramReplacer {
     #[instrument(skip(self))]
     fn transform_url(&self, url: &str) -> ReplaceResult<String> {
         debug!("Transforming Instagram URL...");
-        self.inner.transform_url(url)
+        let mut url = Url::parse(url)?;
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|s| s.map(str::to_owned).collect())
+            .unwrap_or_default();
+        // Drop the profile name from `/<user>/p/<id>` style links
+        let segments = match segments.as_slice() {
+            [profile, post_type, ..]
+                if !INSTAGRAM_TOP_LEVEL_PATHS.contains(&profile.as_str())
+                    && INSTAGRAM_POST_TYPES.contains(&post_type.as_str()) =>
+            {
+                &segments[1..]
+            }
+            _ => &segments[..],
+        };
+        let path = segments
+            .iter()
+            .enumerate()
+            .map(|(idx, segment)| match segment.as_str() {
+                "reels" if idx == 0 => "reel",
+                segment => segment,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        url.set_path(&path);
+        self.inner.transform_url(url.as_str())
     }
 }
 
@@ -60,7 +119,8 @@ impl InstagramConfig {
         domain_regex: &str,
         strip_query: bool,
     ) -> ReplaceConfigResult<Self> {
-        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?;
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?
+            .keep_query_params(INSTAGRAM_KEPT_QUERY);
         Ok(Self { inner: config })
     }
 }
@@ -71,13 +131,30 @@ impl From<InstagramConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for InstagramConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for InstagramConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        let front_end = value
+            .custom_config
+            .get(INSTAGRAM_FRONT_END_OPTION)
+            .map(|front_end| {
+                InstagramFrontEnd::try_from(front_end.as_str())
+                    .map(|f| f.domain())
+                    .map_err(|_| ReplaceConfigError::InvalidOption(front_end.to_owned()))
+            })
+            .transpose()?;
         Self::new(
             value
                 .new_domain
                 .clone()
+                .or(front_end)
                 .unwrap_or(instagram_default_new_domain()),
             value
                 .regex
@@ -127,8 +204,79 @@ mod test {
         let url = "https://www.instagram.com/reel/DCQBM9npSBK/?igsh=c2JxNzRidGk1bWhx";
         let expected = "https://ddinstagram.com/reel/DCQBM9npSBK/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_new_path_shapes() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://www.instagram.com/reels/DCQBM9npSBK/",
+                "https://ddinstagram.com/reel/DCQBM9npSBK/",
+            ),
+            (
+                "https://www.instagram.com/tv/CdLz7BXFJxU/?utm_source=ig_web_copy_link",
+                "https://ddinstagram.com/tv/CdLz7BXFJxU/",
+            ),
+            (
+                "https://www.instagram.com/share/BAHFlkvY_m/",
+                "https://ddinstagram.com/share/BAHFlkvY_m/",
+            ),
+            (
+                "https://www.instagram.com/share/reel/BAHFlkvY_m/",
+                "https://ddinstagram.com/share/reel/BAHFlkvY_m/",
+            ),
+            (
+                "https://www.instagram.com/some.user_1/p/C9fT3xAy0Qd/",
+                "https://ddinstagram.com/p/C9fT3xAy0Qd/",
+            ),
+            (
+                "https://www.instagram.com/some.user_1/reel/DCQBM9npSBK/",
+                "https://ddinstagram.com/reel/DCQBM9npSBK/",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keep_carousel_index() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.instagram.com/p/C9fT3xAy0Qd/?img_index=3&igsh=c2JxNzRidGk1bWhx";
+        let expected = "https://ddinstagram.com/p/C9fT3xAy0Qd/?img_index=3";
+
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_front_end_option() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = LinkReplacerConfig::default();
+        config
+            .custom_config
+            .insert("front_end".into(), "kkinstagram".into());
+        let test_replacer = InstagramReplacer::new((&config).try_into()?);
+        let url = "https://www.instagram.com/p/C9fT3xAy0Qd/";
+        let expected = "https://kkinstagram.com/p/C9fT3xAy0Qd/";
+
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        config
+            .custom_config
+            .insert("front_end".into(), "notinstafix".into());
+        assert!(InstagramConfig::try_from(&config).is_err());
+        Ok(())
+    }
 }
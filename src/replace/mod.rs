@@ -1,166 +1,408 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::str::FromStr;
+use std::time::Duration;
 
-pub use crate::{LinkReplacerConfig, ReplacerConfig};
+pub use crate::{Config, LinkReplacerConfig, ProcessingConfig, ReplacerConfig};
 pub(crate) use base::{
-    LinkProcessor, LinkReplacer, ProcessorConfig, ReplaceConfigError, ReplaceConfigResult,
-    ReplaceError, ReplaceResult,
+    AsyncLinkReplacer, LinkProcessor, LinkReplacer, ProcessorConfig, ReplaceConfigError,
+    ReplaceConfigResult, ReplaceError, ReplaceMode, ReplaceResult,
 };
-use fancy_regex::{Captures, Regex, Replacer};
+use fancy_regex::Regex;
+use futures::future::join_all;
 use strum::EnumString;
-use tracing::{info, instrument, warn};
+use tokio::time::{timeout_at, Instant};
+use tracing::{debug, info, instrument, warn};
+use url::Url;
 
 mod amazon;
 mod base;
+mod bilibili;
 mod bsky;
+mod canonical;
+mod corpus;
+mod deviantart;
+mod facebook;
+mod fediverse;
+mod furaffinity;
+mod host_index;
 mod instagram;
+mod music;
+mod niconico;
+mod pattern;
 mod pixiv;
+mod pool;
 mod reddit;
-mod reddit_media;
+mod report;
+mod resolver;
+mod threads;
 mod tiktok;
+mod tumblr;
+mod twitch;
 mod twitter;
+mod unwrap;
 mod youtube;
 
 use amazon::AmazonReplacer;
+pub(super) use bilibili::BilibiliReplacer;
 pub(super) use bsky::BskyReplacer;
+use canonical::canonicalize;
+use corpus::SAMPLE_URLS;
+pub(super) use deviantart::DeviantArtReplacer;
+pub(super) use facebook::FacebookReplacer;
+use fediverse::FediverseReplacer;
+pub(super) use furaffinity::FurAffinityReplacer;
+use host_index::{regex_domain, HostIndex};
 pub(super) use instagram::InstagramReplacer;
+pub(super) use music::MusicReplacer;
+pub(super) use niconico::NiconicoReplacer;
+use pattern::{build_regex, check_pattern, check_patterns, BacktrackLimit};
 pub(super) use pixiv::PixivReplacer;
 pub(super) use reddit::RedditReplacer;
-use reddit_media::RedditMediaReplacer;
+use resolver::LinkResolver;
+pub(super) use threads::ThreadsReplacer;
 pub(super) use tiktok::TikTokReplacer;
+pub(super) use tumblr::TumblrReplacer;
+pub(super) use twitch::TwitchReplacer;
 pub(super) use twitter::TwitterReplacer;
+use unwrap::LinkUnwrapper;
 pub(super) use youtube::YoutubeReplacer;
 
 pub use amazon::AmazonConfig;
+pub use fediverse::FediverseConfig;
+pub use pool::ProcessingPool;
+pub use report::{LinkReport, MessageReport};
+pub use resolver::ResolverConfig;
+pub use unwrap::UnwrapConfig;
 
 #[derive(Debug, EnumString, PartialEq)]
 enum ReplacerType {
+    #[strum(ascii_case_insensitive)]
+    Bilibili,
     #[strum(ascii_case_insensitive)]
     Bsky,
     #[strum(ascii_case_insensitive)]
+    DeviantArt,
+    #[strum(ascii_case_insensitive)]
+    Facebook,
+    #[strum(ascii_case_insensitive)]
+    FurAffinity,
+    #[strum(ascii_case_insensitive)]
     Instagram,
     #[strum(ascii_case_insensitive)]
+    Music,
+    #[strum(ascii_case_insensitive)]
+    Niconico,
+    #[strum(ascii_case_insensitive)]
     Pixiv,
     #[strum(ascii_case_insensitive)]
     Reddit,
     #[strum(ascii_case_insensitive)]
+    Threads,
+    #[strum(ascii_case_insensitive)]
     TikTok,
     #[strum(ascii_case_insensitive)]
+    Tumblr,
+    #[strum(ascii_case_insensitive)]
+    Twitch,
+    #[strum(ascii_case_insensitive)]
     Twitter,
     #[strum(ascii_case_insensitive)]
     Youtube,
 }
 
-type BoxedLinkReplacer = Box<dyn LinkReplacer + 'static + Sync + Send>;
+type BoxedLinkReplacer = Box<dyn AsyncLinkReplacer + 'static>;
 
 impl ReplacerType {
     pub fn create_type(
         &self,
         config: &LinkReplacerConfig,
+        backtrack_limit: usize,
     ) -> ReplaceConfigResult<BoxedLinkReplacer> {
         let replacer: BoxedLinkReplacer = match self {
-            Self::Bsky => Box::new(BskyReplacer::new(config.try_into()?)),
-            Self::Instagram => Box::new(InstagramReplacer::new(config.try_into()?)),
-            Self::Pixiv => Box::new(PixivReplacer::new(config.try_into()?)),
-            Self::Reddit => Box::new(RedditReplacer::new(config.try_into()?)),
-            Self::TikTok => Box::new(TikTokReplacer::new(config.try_into()?)),
-            Self::Twitter => Box::new(TwitterReplacer::new(config.try_into()?)),
-            Self::Youtube => Box::new(YoutubeReplacer::new(config.try_into()?)),
+            Self::Bilibili => Box::new(BilibiliReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Bsky => Box::new(BskyReplacer::new(limited(config, backtrack_limit)?)),
+            Self::DeviantArt => {
+                Box::new(DeviantArtReplacer::new(limited(config, backtrack_limit)?))
+            }
+            Self::Facebook => Box::new(FacebookReplacer::new(limited(config, backtrack_limit)?)),
+            Self::FurAffinity => {
+                Box::new(FurAffinityReplacer::new(limited(config, backtrack_limit)?))
+            }
+            Self::Instagram => Box::new(InstagramReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Music => Box::new(MusicReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Niconico => Box::new(NiconicoReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Pixiv => Box::new(PixivReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Reddit => Box::new(RedditReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Threads => Box::new(ThreadsReplacer::new(limited(config, backtrack_limit)?)),
+            Self::TikTok => Box::new(TikTokReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Tumblr => Box::new(TumblrReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Twitch => Box::new(TwitchReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Twitter => Box::new(TwitterReplacer::new(limited(config, backtrack_limit)?)),
+            Self::Youtube => Box::new(YoutubeReplacer::new(limited(config, backtrack_limit)?)),
         };
         Ok(replacer)
     }
+
+    /// Domains the default regex of the replacer matches, subdomains included
+    fn hosts(&self) -> &'static [&'static str] {
+        match self {
+            Self::Bilibili => &["bilibili.com", "b23.tv"],
+            Self::Bsky => &["bsky.app"],
+            Self::DeviantArt => &["deviantart.com"],
+            Self::Facebook => &["facebook.com", "fb.watch"],
+            Self::FurAffinity => &["furaffinity.net"],
+            Self::Instagram => &["instagram.com"],
+            Self::Music => &[
+                "open.spotify.com",
+                "music.apple.com",
+                "tidal.com",
+                "deezer.com",
+                "music.youtube.com",
+            ],
+            Self::Niconico => &["nicovideo.jp", "nico.ms"],
+            Self::Pixiv => &["pixiv.net"],
+            Self::Reddit => &["reddit.com", "redd.it"],
+            Self::Threads => &["threads.net", "threads.com"],
+            Self::TikTok => &["tiktok.com"],
+            Self::Tumblr => &["tumblr.com"],
+            Self::Twitch => &["twitch.tv"],
+            Self::Twitter => &["x.com", "twitter.com"],
+            Self::Youtube => &["youtube.com"],
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-struct MessageReplacer<'a, T> {
-    replacers: &'a [BoxedLinkReplacer],
-    error: Option<T>,
+/// A replacer along with the name it was configured under
+#[derive(Debug)]
+struct NamedReplacer {
+    name: String,
+    replacer: BoxedLinkReplacer,
+    /// Domains the replacer is limited to, if known
+    hosts: Option<Vec<String>>,
 }
 
-impl<'a> MessageReplacer<'a, ReplaceError> {
-    pub fn new(replacers: &'a [BoxedLinkReplacer]) -> Self {
+impl NamedReplacer {
+    fn new(name: &str, replacer: BoxedLinkReplacer, hosts: Option<Vec<String>>) -> Self {
+        let name = name.to_owned();
         Self {
-            replacers,
-            error: None,
+            name,
+            replacer,
+            hosts,
         }
     }
+}
 
-    fn replace_append_impl(&mut self, caps: &Captures<'_>) -> ReplaceResult<String> {
-        let url = caps[0].to_string();
-        for replacer in self.replacers {
-            if replacer.is_match(&url)? {
-                return replacer.process_url(&url);
-            }
-        }
-        Ok(url)
-    }
+/// Creates the config of a built-in replacer with regexes that give up after
+/// `backtrack_limit` backtracking steps
+fn limited<T>(config: &LinkReplacerConfig, backtrack_limit: usize) -> ReplaceConfigResult<T>
+where
+    T: for<'a> TryFrom<&'a LinkReplacerConfig, Error = ReplaceConfigError> + BacktrackLimit,
+{
+    T::try_from(config)?.backtrack_limit(backtrack_limit)
+}
 
-    pub fn error(&self) -> Option<ReplaceError> {
-        self.error.clone()
+/// The domains a replacer is limited to, worked out from its regex when the
+/// default one was overridden
+fn replacer_hosts(regex: Option<&str>, default_hosts: &[&str]) -> Option<Vec<String>> {
+    match regex {
+        Some(regex) => regex_domain(regex).map(|domain| vec![domain]),
+        None => Some(default_hosts.iter().map(|host| host.to_string()).collect()),
     }
 }
 
-impl Replacer for MessageReplacer<'_, ReplaceError> {
-    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
-        let rep = match self.replace_append_impl(caps) {
-            Ok(rep) => rep,
-            Err(err) => {
-                self.error = Some(err);
-                caps[0].to_string()
-            }
-        };
-        dst.push_str(&rep);
-    }
+/// A link found in a message along with the replacer that will fix it
+#[derive(Debug)]
+struct LinkSpan {
+    range: Range<usize>,
+    /// The link after resolving, unwrapping and canonicalizing it
+    target: String,
+    /// Position of the replacer that matched and where its link is in the target
+    matched: Option<(usize, Range<usize>)>,
+    /// The fixed link, when the replacer didn't have to wait for anything
+    fixed: Option<ReplaceResult<String>>,
+    error: Option<ReplaceError>,
 }
 
-static HTTP_URL_RE: &str =
-    r"(?:https?://)?(?:[a-zA-Z0-9-]+\.)+[a-zA-Z]{2,}(?:/[^\s*~`|>\[\]#()]*)?";
+/// Links end at a `#` unless it's followed by a number, which some sites use
+/// to point at a page of a post
+static HTTP_URL_RE: &str = r"at://[^\s*~`|>\[\]#()]+|(?:https?://)?(?:[a-zA-Z0-9-]+\.)+[a-zA-Z]{2,}(?:/[^\s*~`|>\[\]#()]*(?:#\d+\b)?)?";
 
 pub struct MessageProcessor {
-    url_processors: Vec<BoxedLinkReplacer>,
+    url_processors: Vec<NamedReplacer>,
+    host_index: HostIndex,
+    link_unwrapper: Option<LinkUnwrapper>,
+    link_resolver: Option<LinkResolver>,
     http_url_regex: Regex,
+    message_timeout: Duration,
+    fix_bare_links: bool,
+    regex_budget: Duration,
+    /// Replacers and options that couldn't be created, by name
+    problems: Vec<(String, ReplaceConfigError)>,
 }
 
 impl MessageProcessor {
     pub fn new(
         config: &ReplacerConfig,
-        reddit_media_re: Option<String>,
         amazon_config: &AmazonConfig,
+        fediverse_config: &FediverseConfig,
+        unwrap_config: &UnwrapConfig,
+        resolver_config: &ResolverConfig,
+        processing_config: &ProcessingConfig,
     ) -> Self {
-        let http_url_regex = Regex::new(HTTP_URL_RE).unwrap();
-        let mut url_processors: Vec<BoxedLinkReplacer> = Vec::new();
-        if let Ok(reddit_media_replacer) = RedditMediaReplacer::new(reddit_media_re)
+        let backtrack_limit = processing_config.regex_backtrack_limit;
+        let http_url_regex = build_regex(HTTP_URL_RE, backtrack_limit).unwrap();
+        let message_timeout = Duration::from_millis(processing_config.message_timeout_ms);
+        let regex_budget = Duration::from_millis(processing_config.regex_budget_ms);
+        let link_unwrapper = LinkUnwrapper::new(unwrap_config);
+        let mut problems = Vec::new();
+        let link_resolver = LinkResolver::new(resolver_config)
+            .map_err(|err| {
+                warn! {%err, "error creating short link resolver"};
+                problems.push(("resolver".to_owned(), err));
+            })
+            .ok()
+            .flatten();
+        // The built-in replacers are always tried before the configured ones
+        let mut url_processors: Vec<NamedReplacer> = Vec::new();
+        if let Ok(amazon_replacer) = check_pattern(&amazon_config.regex)
+            .and_then(|()| AmazonReplacer::new(amazon_config))
+            .and_then(|replacer| replacer.backtrack_limit(backtrack_limit))
             .map(Box::new)
-            .map_err(|err| warn! {%err, "error creating reddit media replacer"})
+            .map_err(|err| {
+                warn! {%err, "error creating amazon shortener"};
+                problems.push(("amazon".to_owned(), err));
+            })
         {
-            url_processors.push(reddit_media_replacer)
+            let amazon_hosts = replacer_hosts(Some(&amazon_config.regex), &[]);
+            url_processors.push(NamedReplacer::new("amazon", amazon_replacer, amazon_hosts))
         }
-        if let Ok(amazon_replacer) = AmazonReplacer::new(amazon_config)
-            .map(Box::new)
-            .map_err(|err| warn! {%err, "error creating amazon shortener"})
+        match check_pattern(&fediverse_config.regex)
+            .and_then(|()| FediverseReplacer::new(fediverse_config))
+            .and_then(|replacer| replacer.backtrack_limit(backtrack_limit))
         {
-            url_processors.push(amazon_replacer)
-        }
-        for (replacer_name, config) in config.iter() {
-            let new_replacer = if let Ok(replacer) = ReplacerType::from_str(replacer_name) {
-                info!("Creating {} replacer...", &replacer_name);
-                replacer.create_type(config)
-            } else {
-                Self::create_custom_replacer(replacer_name, config)
+            Ok(fediverse_replacer) if fediverse_replacer.has_instances() => {
+                info!("Creating fediverse replacer...");
+                let instances = fediverse_replacer.instances().map(str::to_owned).collect();
+                url_processors.push(NamedReplacer::new(
+                    "fediverse",
+                    Box::new(fediverse_replacer),
+                    Some(instances),
+                ))
             }
-            .map_err(|reason| warn! {%reason, "creating replacer"});
-            if let Ok(new_replacer) = new_replacer {
-                url_processors.push(new_replacer);
+            Ok(_) => debug!("no fediverse instances configured"),
+            Err(err) => {
+                warn! {%err, "error creating fediverse replacer"};
+                problems.push(("fediverse".to_owned(), err));
             }
         }
-        Self {
+        let mut configured: Vec<_> = config.iter().collect();
+        configured.sort_by(|(a_name, a), (b_name, b)| {
+            b.priority.cmp(&a.priority).then_with(|| a_name.cmp(b_name))
+        });
+        for (replacer_name, config) in configured {
+            let new_replacer = check_patterns(config)
+                .and_then(|()| {
+                    if let Ok(replacer) = ReplacerType::from_str(replacer_name) {
+                        info!("Creating {} replacer...", &replacer_name);
+                        let hosts = replacer_hosts(config.regex.as_deref(), replacer.hosts());
+                        replacer
+                            .create_type(config, backtrack_limit)
+                            .map(|r| (r, hosts))
+                    } else {
+                        let hosts = replacer_hosts(config.regex.as_deref(), &[]);
+                        Self::create_custom_replacer(replacer_name, config, backtrack_limit)
+                            .map(|r| (r, hosts))
+                    }
+                })
+                .map_err(|reason| {
+                    warn! {%reason, replacer = replacer_name, "creating replacer"};
+                    problems.push((replacer_name.clone(), reason));
+                });
+            if let Ok((new_replacer, hosts)) = new_replacer {
+                url_processors.push(NamedReplacer::new(replacer_name, new_replacer, hosts));
+            }
+        }
+        let host_index = HostIndex::new(url_processors.iter().map(|named| named.hosts.as_deref()));
+        let processor = Self {
             http_url_regex,
+            link_unwrapper,
+            link_resolver,
             url_processors,
+            host_index,
+            message_timeout,
+            fix_bare_links: processing_config.fix_bare_links,
+            regex_budget,
+            problems,
+        };
+        processor.warn_overlaps();
+        processor
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let mut processor = Self::new(
+            &config.replacers,
+            &config.amazon,
+            &config.fediverse,
+            &config.unwrap,
+            &config.resolver,
+            &config.processing,
+        );
+        if config.reddit_media_regex.is_some() {
+            let err = ReplaceConfigError::InvalidOption(
+                "reddit.com/media links are unwrapped now, remove reddit_media_regex".to_owned(),
+            );
+            warn! {%err, "ignoring reddit_media_regex"};
+            processor
+                .problems
+                .push(("reddit_media_regex".to_owned(), err));
+        }
+        processor
+    }
+
+    /// The replacers and options from the config that were left out because
+    /// they couldn't be created
+    pub fn problems(&self) -> &[(String, ReplaceConfigError)] {
+        &self.problems
+    }
+
+    /// Names of the replacers in the order they're tried
+    pub fn replacer_names(&self) -> impl Iterator<Item = &str> {
+        self.url_processors.iter().map(|named| named.name.as_str())
+    }
+
+    /// Sample links that more than one replacer matches, along with the names
+    /// of those replacers in the order they're tried
+    pub fn find_overlaps(&self) -> Vec<(&'static str, Vec<&str>)> {
+        SAMPLE_URLS
+            .iter()
+            .filter_map(|url| {
+                let names: Vec<&str> = self
+                    .candidates(url)
+                    .filter(|(_, named)| named.replacer.is_match(url).unwrap_or(false))
+                    .map(|(_, named)| named.name.as_str())
+                    .collect();
+                (names.len() > 1).then_some((*url, names))
+            })
+            .collect()
+    }
+
+    fn warn_overlaps(&self) {
+        for (url, names) in self.find_overlaps() {
+            warn! {
+                %url,
+                replacers = ?names,
+                used = names[0],
+                "more than one replacer matches this link, set a priority to pick one"
+            };
         }
     }
 
     fn create_custom_replacer(
         name: &str,
         config: &LinkReplacerConfig,
+        backtrack_limit: usize,
     ) -> ReplaceConfigResult<BoxedLinkReplacer> {
         if let Some(new_domain) = config.new_domain.clone() {
             if let (Some(regex), Some(domain_re), Some(strip_query)) = (
@@ -169,7 +411,8 @@ impl MessageProcessor {
                 config.strip_query,
             ) {
                 info!("Creating custom replacer {}...", name);
-                let config = ProcessorConfig::new(new_domain, regex, domain_re, strip_query)?;
+                let config = ProcessorConfig::new(new_domain, regex, domain_re, strip_query)?
+                    .backtrack_limit(backtrack_limit)?;
                 let custom_replacer: BoxedLinkReplacer = Box::new(LinkProcessor::new(config));
                 Ok(custom_replacer)
             } else if config.regex.is_none() {
@@ -186,20 +429,210 @@ impl MessageProcessor {
         }
     }
 
+    /// Looks up where the short links in a message redirect to. Links that
+    /// aren't resolved before the deadline are left as they are
     #[instrument(level = "debug", skip(self))]
-    pub fn process_message(&self, msg: &str) -> ReplaceResult<Option<String>> {
-        let mut message_replacer = MessageReplacer::new(&self.url_processors);
-        let new_msg = self
+    async fn resolve_links(&self, msg: &str, deadline: Instant) -> HashMap<String, String> {
+        let Some(resolver) = self.link_resolver.as_ref() else {
+            return HashMap::new();
+        };
+        let short_links: Vec<Cow<'_, str>> = self
             .http_url_regex
-            .replace_all(msg, message_replacer.by_ref())
-            .to_string();
-        if let Some(process_err) = message_replacer.error() {
-            Err(process_err)
-        } else if msg == new_msg {
-            Ok(None)
+            .find_iter(msg)
+            .filter_map(|m| m.ok())
+            .filter_map(|m| self.normalize_link(m.as_str()))
+            .filter(|url| resolver.can_resolve(url))
+            .collect();
+        let resolved = join_all(short_links.iter().map(|url| async move {
+            timeout_at(deadline, resolver.resolve(url))
+                .await
+                .unwrap_or_else(|_| {
+                    warn! {%url, "short link was not resolved before the deadline"};
+                    None
+                })
+        }))
+        .await;
+        short_links
+            .into_iter()
+            .zip(resolved)
+            .filter_map(|(url, resolved)| resolved.map(|resolved| (url.into_owned(), resolved)))
+            .collect()
+    }
+
+    pub async fn process_message(&self, msg: &str) -> ReplaceResult<Option<String>> {
+        Ok(self.report_message(msg).await?.reply())
+    }
+
+    /// Processes a message and reports what happened to each of its links
+    pub async fn report_message(&self, msg: &str) -> ReplaceResult<MessageReport> {
+        let deadline = Instant::now() + self.message_timeout;
+        let resolved = self.resolve_links(msg, deadline).await;
+        self.process_resolved_message(msg, &resolved, deadline)
+            .await
+    }
+
+    /// Adds `https://` to links typed without a scheme, or returns `None` if
+    /// those shouldn't be fixed
+    fn normalize_link<'a>(&self, url: &'a str) -> Option<Cow<'a, str>> {
+        if ["http://", "https://", "at://"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme))
+        {
+            Some(Cow::Borrowed(url))
+        } else if self.fix_bare_links {
+            Some(Cow::Owned(format!("https://{url}")))
         } else {
-            Ok(Some(new_msg))
+            None
+        }
+    }
+
+    /// The replacers that may match the link, in the order they're tried
+    fn candidates(&self, url: &str) -> impl Iterator<Item = (usize, &NamedReplacer)> {
+        let host = Url::parse(url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .and_then(|url| url.host_str().map(str::to_owned));
+        self.host_index
+            .candidates(host.as_deref())
+            .into_iter()
+            .map(|position| (position, &self.url_processors[position]))
+    }
+
+    fn find_links(
+        &self,
+        msg: &str,
+        resolved: &HashMap<String, String>,
+    ) -> ReplaceResult<Vec<LinkSpan>> {
+        let started = Instant::now();
+        let mut spans = Vec::new();
+        for link in self.http_url_regex.find_iter(msg) {
+            let link = link.map_err(|err| ReplaceError::Config(err.into()))?;
+            let Some(url) = self.normalize_link(link.as_str()) else {
+                continue;
+            };
+            let source_url = resolved
+                .get(url.as_ref())
+                .map_or(url.as_ref(), String::as_str);
+            let unwrapped = self
+                .link_unwrapper
+                .as_ref()
+                .and_then(|unwrapper| unwrapper.unwrap(source_url));
+            let target = canonicalize(unwrapped.as_deref().unwrap_or(source_url)).into_owned();
+            let mut matched = None;
+            let mut error = None;
+            let mut over_budget = false;
+            for (position, named) in self.candidates(&target) {
+                let found = named.replacer.find_match(&target);
+                if started.elapsed() > self.regex_budget {
+                    let err = ReplaceError::RegexBudget(named.name.clone());
+                    warn! {%err, replacer = named.name, "skipping the rest of the message"};
+                    error = Some(err);
+                    over_budget = true;
+                    break;
+                }
+                match found {
+                    Ok(Some(range)) => matched = Some((position, range)),
+                    Ok(None) => continue,
+                    Err(err) => {
+                        warn! {%err, replacer = named.name, "could not match link"};
+                        error = Some(err);
+                    }
+                }
+                break;
+            }
+            spans.push(LinkSpan {
+                range: link.range(),
+                target,
+                matched,
+                fixed: None,
+                error,
+            });
+            if over_budget {
+                break;
+            }
         }
+        Ok(spans)
+    }
+
+    /// Finds the links of a message and fixes the ones whose replacers don't
+    /// have to wait for anything. This is all of the CPU-bound work on a message
+    fn match_links(
+        &self,
+        msg: &str,
+        resolved: &HashMap<String, String>,
+    ) -> ReplaceResult<Vec<LinkSpan>> {
+        let mut spans = self.find_links(msg, resolved)?;
+        for span in &mut spans {
+            if let Some((position, range)) = span.matched.clone() {
+                let replacer = &self.url_processors[position].replacer;
+                span.fixed = replacer.process_match_now(&span.target, range);
+            }
+        }
+        Ok(spans)
+    }
+
+    /// Fixes the links that are left concurrently. Links that fail or aren't
+    /// fixed before the message deadline are left as they are
+    async fn fix_links(&self, msg: &str, spans: Vec<LinkSpan>, deadline: Instant) -> MessageReport {
+        let fixed = join_all(spans.iter().map(|span| async move {
+            let (position, range) = span.matched.clone()?;
+            if let Some(fixed) = span.fixed.clone() {
+                return Some(fixed);
+            }
+            let replacer = &self.url_processors[position].replacer;
+            let fixed = timeout_at(deadline, replacer.process_match(&span.target, range))
+                .await
+                .unwrap_or(Err(ReplaceError::Timeout));
+            Some(fixed)
+        }))
+        .await;
+
+        let links = spans
+            .into_iter()
+            .zip(fixed)
+            .map(|(span, fixed)| {
+                let original = msg[span.range.clone()].to_owned();
+                let (replacer, mode) = span
+                    .matched
+                    .map(|(position, _)| &self.url_processors[position])
+                    .map_or((None, ReplaceMode::default()), |named| {
+                        (Some(named.name.clone()), named.replacer.mode())
+                    });
+                let (fixed, error) = match fixed {
+                    _ if span.error.is_some() => (None, span.error),
+                    Some(Ok(new_url)) => (Some(new_url), None),
+                    Some(Err(err)) => {
+                        if let ReplaceError::Timeout = err {
+                            warn! {url = %original, "link was not fixed before the deadline"};
+                        }
+                        (None, Some(err))
+                    }
+                    None => (None, None),
+                };
+                LinkReport {
+                    span: span.range,
+                    original,
+                    target: span.target,
+                    fixed,
+                    replacer,
+                    mode,
+                    error,
+                }
+            })
+            .collect();
+        MessageReport::new(msg, links)
+    }
+
+    /// Fixes every link in the message
+    #[instrument(level = "debug", skip(self))]
+    async fn process_resolved_message(
+        &self,
+        msg: &str,
+        resolved: &HashMap<String, String>,
+        deadline: Instant,
+    ) -> ReplaceResult<MessageReport> {
+        let spans = self.match_links(msg, resolved)?;
+        Ok(self.fix_links(msg, spans, deadline).await)
     }
 }
 
@@ -207,6 +640,54 @@ impl MessageProcessor {
 mod test {
 
     use super::*;
+    use crate::{init_tests, spawn_redirect_server};
+    use futures::future::BoxFuture;
+    use std::sync::Arc;
+
+    /// Stands in for a replacer that has to wait on the network
+    #[derive(Debug)]
+    struct DelayedReplacer {
+        delay: Duration,
+    }
+
+    impl AsyncLinkReplacer for DelayedReplacer {
+        fn find_match(&self, url: &str) -> ReplaceResult<Option<Range<usize>>> {
+            Ok(url
+                .starts_with("https://slow.example.com/")
+                .then_some(0..url.len()))
+        }
+
+        fn process_match<'a>(
+            &'a self,
+            url: &'a str,
+            _range: Range<usize>,
+        ) -> BoxFuture<'a, ReplaceResult<String>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.delay).await;
+                Ok(url.replace("slow.example.com", "fixed.example.com"))
+            })
+        }
+    }
+
+    fn push_replacer(processor: &mut MessageProcessor, named: NamedReplacer) {
+        processor.url_processors.push(named);
+        processor.host_index = HostIndex::new(
+            processor
+                .url_processors
+                .iter()
+                .map(|named| named.hosts.as_deref()),
+        );
+    }
+
+    fn create_delayed_processor(delay_ms: u64, timeout_ms: u64) -> ReplaceResult<MessageProcessor> {
+        let mut processor = create_processor()?;
+        let delayed = Box::new(DelayedReplacer {
+            delay: Duration::from_millis(delay_ms),
+        });
+        push_replacer(&mut processor, NamedReplacer::new("delayed", delayed, None));
+        processor.message_timeout = Duration::from_millis(timeout_ms);
+        Ok(processor)
+    }
 
     fn create_processor() -> ReplaceResult<MessageProcessor> {
         let mut config = ReplacerConfig::new();
@@ -215,7 +696,19 @@ mod test {
             LinkReplacerConfig::new("d.tnktok.com".into()),
         );
         config.insert("youtube".into(), LinkReplacerConfig::new("youtu.be".into()));
-        let processor = MessageProcessor::new(&config, None, &AmazonConfig::default());
+        config.insert("bsky".into(), LinkReplacerConfig::new("bskyx.app".into()));
+        config.insert("music".into(), LinkReplacerConfig::new("song.link".into()));
+        let processor = MessageProcessor::new(
+            &config,
+            &AmazonConfig::default(),
+            &FediverseConfig {
+                instances: vec!["mastodon.social".into()],
+                ..Default::default()
+            },
+            &UnwrapConfig::default(),
+            &ResolverConfig::default(),
+            &ProcessingConfig::default(),
+        );
         Ok(processor)
     }
 
@@ -227,7 +720,7 @@ mod test {
         let expected =
             "Test message with a TikTok link ||https://d.tnktok.com/t/ZTYXjHYeg/|| in it.";
 
-        let result = processor.process_message(message);
+        let result = processor.process_message(message).await;
         assert!(result.is_ok());
         let result = result.unwrap();
         assert!(result.is_some());
@@ -241,7 +734,7 @@ mod test {
         let message = "Test message with **multiple** (https://www.tiktok.com/t/ZTYX2qUvY/) types of links ||https://youtube.com/shorts/xFnfOdb35FI/|| in it.";
         let expected = "Test message with **multiple** (https://d.tnktok.com/t/ZTYX2qUvY/) types of links ||https://youtu.be/xFnfOdb35FI/|| in it.";
 
-        let result = processor.process_message(message);
+        let result = processor.process_message(message).await;
         assert!(result.is_ok());
         let result = result.unwrap();
         assert!(result.is_some());
@@ -249,12 +742,518 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_at_uri_message_replace() -> ReplaceResult<()> {
+        let processor = create_processor()?;
+        let message =
+            "Look at at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.post/3ldpen4om622h please";
+        let expected = "Look at https://bskyx.app/profile/did:plc:z72i7hdynmk6r22z27h6tvur/post/3ldpen4om622h please";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fediverse_message_replace() -> ReplaceResult<()> {
+        let processor = create_processor()?;
+        let message = "Toot https://mastodon.social/@Gargron/113749447366429385 and https://example.social/@Gargron/113749447366429385";
+        let expected = "Toot https://fxmastodon.net/mastodon.social/@Gargron/113749447366429385 and https://example.social/@Gargron/113749447366429385";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_mode_message() -> ReplaceResult<()> {
+        let processor = create_processor()?;
+        let message = "Listen to https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT?si=abc123 and ||https://www.tiktok.com/t/ZTYXjHYeg/||";
+        let expected = "Listen to https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT?si=abc123 and ||https://d.tnktok.com/t/ZTYXjHYeg/||\nhttps://song.link/https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unwrapped_message_replace() -> ReplaceResult<()> {
+        let processor = create_processor()?;
+        let message = "Redirected https://www.google.com/url?q=https%3A%2F%2Fwww.tiktok.com%2Ft%2FZTYXjHYeg%2F&sa=D and https://href.li/?https://example.com/page";
+        // Links no replacer handles are left as they are, even when wrapped
+        let expected = "Redirected https://d.tnktok.com/t/ZTYXjHYeg/ and https://href.li/?https://example.com/page";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolved_message_replace() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "Short https://vm.tiktok.com/ZMhvqKJ4B/ and https://t.co/AbCdEf123";
+        let resolved = HashMap::from([
+            (
+                "https://vm.tiktok.com/ZMhvqKJ4B/".to_owned(),
+                "https://www.tiktok.com/t/ZTYXjHYeg/".to_owned(),
+            ),
+            (
+                "https://t.co/AbCdEf123".to_owned(),
+                "https://example.com/page".to_owned(),
+            ),
+        ]);
+        // Short links that don't resolve to a supported site are left alone
+        let expected = "Short https://d.tnktok.com/t/ZTYXjHYeg/ and https://t.co/AbCdEf123";
+
+        let deadline = Instant::now() + processor.message_timeout;
+        let result = processor
+            .process_resolved_message(message, &resolved, deadline)
+            .await?
+            .reply();
+        assert_eq!(result.as_deref(), Some(expected));
+        assert!(processor.resolve_links(message, deadline).await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_async_replacers_run_concurrently() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_delayed_processor(200, 5000)?;
+        let message = "https://slow.example.com/1 https://slow.example.com/2 https://slow.example.com/3 https://www.tiktok.com/t/ZTYXjHYeg/";
+        let expected = "https://fixed.example.com/1 https://fixed.example.com/2 https://fixed.example.com/3 https://d.tnktok.com/t/ZTYXjHYeg/";
+
+        let start = std::time::Instant::now();
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        assert!(start.elapsed() < Duration::from_millis(500));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pool_only_blocks_for_matching() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = Arc::new(create_delayed_processor(300, 5000)?);
+        let pool = ProcessingPool::new(1);
+
+        // Waiting on a replacer doesn't hold on to the only worker
+        let start = std::time::Instant::now();
+        let reports = join_all((0..4).map(|i| {
+            pool.report_message(processor.clone(), format!("https://slow.example.com/{i}"))
+        }))
+        .await;
+        assert!(start.elapsed() < Duration::from_millis(900));
+        for (i, report) in reports.into_iter().enumerate() {
+            let reply = format!("https://fixed.example.com/{i}");
+            assert_eq!(report?.reply(), Some(reply));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_page_fragment_message_replace() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = ReplacerConfig::new();
+        config.insert("pixiv".into(), LinkReplacerConfig::default());
+        let processor = create_processor_from(&config);
+        let message = "Page two https://www.pixiv.net/en/artworks/123456#2 and not a heading https://www.pixiv.net/artworks/123456#comments";
+        let expected = "Page two https://phixiv.net/en/artworks/123456/2 and not a heading https://phixiv.net/artworks/123456#comments";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_deadline() -> ReplaceResult<()> {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let mut processor = create_processor()?;
+        processor.link_resolver = LinkResolver::new(&ResolverConfig {
+            enabled: true,
+            hosts: vec!["127.0.0.1".into()],
+            timeout_ms: 5000,
+            ..Default::default()
+        })?;
+        // Message links never have an IP address as host
+        processor.http_url_regex = build_regex(r"https?://[^\s]+", 1_000)?;
+        processor.message_timeout = Duration::from_millis(200);
+        let message = format!(
+            "{}/slow and https://www.tiktok.com/t/ZTYXjHYeg/",
+            server.address
+        );
+        let expected = format!(
+            "{}/slow and https://d.tnktok.com/t/ZTYXjHYeg/",
+            server.address
+        );
+
+        let start = std::time::Instant::now();
+        let result = processor.process_message(&message).await?;
+        assert_eq!(result, Some(expected));
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_message_deadline() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_delayed_processor(2000, 100)?;
+        let message = "https://slow.example.com/1 and https://www.tiktok.com/t/ZTYXjHYeg/";
+        let expected = "https://slow.example.com/1 and https://d.tnktok.com/t/ZTYXjHYeg/";
+
+        let start = std::time::Instant::now();
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        Ok(())
+    }
+
+    fn create_processor_from(config: &ReplacerConfig) -> MessageProcessor {
+        MessageProcessor::new(
+            config,
+            &AmazonConfig::default(),
+            &FediverseConfig {
+                instances: vec!["mastodon.social".into()],
+                ..Default::default()
+            },
+            &UnwrapConfig::default(),
+            &ResolverConfig::default(),
+            &ProcessingConfig::default(),
+        )
+    }
+
+    fn replacer_names(processor: &MessageProcessor) -> Vec<&str> {
+        processor.replacer_names().collect()
+    }
+
+    #[tokio::test]
+    async fn test_default_replacers_do_not_overlap() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = ReplacerConfig::new();
+        for name in crate::config::DEFAULT_MAPPINGS.iter().chain(&["music"]) {
+            config.insert(name.to_string(), LinkReplacerConfig::default());
+        }
+        let processor = create_processor_from(&config);
+
+        assert_eq!(processor.url_processors.len(), config.len() + 2);
+        assert!(processor.find_overlaps().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replacer_priority() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = ReplacerConfig::new();
+        config.insert("tiktok".into(), LinkReplacerConfig::default());
+        config.insert("twitter".into(), LinkReplacerConfig::default());
+        let mut other_tiktok = LinkReplacerConfig::new("tiktxk.com".into());
+        other_tiktok
+            .set_regex(r"https?://(www\.)?tiktok\.com/t/\w+[^\s]*".into())
+            .set_domain_re(r"(www\.)?tiktok\.com".into())
+            .set_strip_query(true);
+        config.insert("other_tiktok".into(), other_tiktok);
+
+        // Equal priorities are tried by name
+        let processor = create_processor_from(&config);
+        assert_eq!(
+            replacer_names(&processor)[2..],
+            ["other_tiktok", "tiktok", "twitter"]
+        );
+        let overlaps = processor.find_overlaps();
+        assert_eq!(
+            overlaps,
+            [(
+                "https://www.tiktok.com/t/ZTYXjHYeg/",
+                vec!["other_tiktok", "tiktok"]
+            )]
+        );
+
+        config.get_mut("tiktok").unwrap().set_priority(10);
+        let processor = create_processor_from(&config);
+        assert_eq!(
+            replacer_names(&processor)[2..],
+            ["tiktok", "other_tiktok", "twitter"]
+        );
+        let result = processor
+            .process_message("https://www.tiktok.com/t/ZTYXjHYeg/")
+            .await?;
+        assert_eq!(result.as_deref(), Some("https://d.tnktok.com/t/ZTYXjHYeg/"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replacers_only_see_their_hosts() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "https://example.com/share?next=https://www.tiktok.com/t/ZTYXjHYeg/ and https://m.tiktok.com/v/7412345678901234567.html";
+        let expected = "https://example.com/share?next=https://www.tiktok.com/t/ZTYXjHYeg/ and https://d.tnktok.com/@/video/7412345678901234567";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_message_report() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "https://www.tiktok.com/t/ZTYXjHYeg/ https://example.com/page https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT https://href.li/?https://example.com/page";
+
+        let report = processor.report_message(message).await?;
+        let links: Vec<_> = report
+            .links
+            .iter()
+            .map(|link| {
+                (
+                    &message[link.span.clone()],
+                    link.fixed.as_deref(),
+                    link.replacer.as_deref(),
+                    link.mode,
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    "https://www.tiktok.com/t/ZTYXjHYeg/",
+                    Some("https://d.tnktok.com/t/ZTYXjHYeg/"),
+                    Some("tiktok"),
+                    ReplaceMode::Replace
+                ),
+                ("https://example.com/page", None, None, ReplaceMode::Replace),
+                (
+                    "https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT",
+                    Some("https://song.link/https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT"),
+                    Some("music"),
+                    ReplaceMode::Append
+                ),
+                (
+                    "https://href.li/?https://example.com/page",
+                    None,
+                    None,
+                    ReplaceMode::Replace
+                ),
+            ]
+        );
+        assert_eq!(report.fixed().count(), 2);
+        assert_eq!(report.failed().count(), 0);
+        assert_eq!(
+            report.reply().as_deref(),
+            Some("https://d.tnktok.com/t/ZTYXjHYeg/ https://example.com/page https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT https://href.li/?https://example.com/page\nhttps://song.link/https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_only_reply() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "listen to this https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT and https://music.apple.com/us/album/some-album/1440857781";
+
+        let report = processor.report_message(message).await?;
+        assert_eq!(
+            report.reply().as_deref(),
+            Some("https://song.link/https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT\nhttps://song.link/https://music.apple.com/us/album/some-album/1440857781")
+        );
+        assert!(!report.suppress_embeds());
+
+        let message = "https://www.tiktok.com/t/ZTYXjHYeg/ https://open.spotify.com/track/4cOdK2wGLETKBW3PvgPWqT";
+        let report = processor.report_message(message).await?;
+        assert!(report.suppress_embeds());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_partial_success() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "Good https://www.tiktok.com/t/ZTYXjHYeg/ bad https://www.youtube.com/watch?feature=share good https://youtube.com/shorts/xFnfOdb35FI";
+        let expected = "Good https://d.tnktok.com/t/ZTYXjHYeg/ bad https://www.youtube.com/watch?feature=share good https://youtu.be/xFnfOdb35FI";
+
+        let report = processor.report_message(message).await?;
+        assert_eq!(report.reply().as_deref(), Some(expected));
+        let failed: Vec<_> = report.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            failed[0].original,
+            "https://www.youtube.com/watch?feature=share"
+        );
+        assert_eq!(failed[0].replacer.as_deref(), Some("youtube"));
+        assert!(matches!(failed[0].error, Some(ReplaceError::NoQueryParams)));
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_failed_links() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut processor = create_processor()?;
+        let mut config = LinkReplacerConfig::new("example.com".into());
+        config
+            .set_regex(r"https?://example\.com/[^\s]+".into())
+            .set_domain_re(r"example\.com".into())
+            .set_strip_query(false);
+        let unchanged = MessageProcessor::create_custom_replacer("unchanged", &config, 1_000)?;
+        push_replacer(
+            &mut processor,
+            NamedReplacer::new("unchanged", unchanged, None),
+        );
+        let message = "https://example.com/page and https://www.youtube.com/watch?feature=share";
+
+        let report = processor.report_message(message).await?;
+        assert_eq!(report.failed().count(), 2);
+        assert!(matches!(
+            report.links[0].error,
+            Some(ReplaceError::UrlNotModified(_))
+        ));
+        assert!(report.reply().is_none());
+        assert!(processor.process_message(message).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_canonical_links() -> ReplaceResult<()> {
+        init_tests().await;
+        let processor = create_processor()?;
+        let message = "https://m.youtube.com/shorts/xFnfOdb35FI and https://WWW.TIKTOK.COM/t/ZTYXjHYeg// but https://www.Example.com/Page";
+        let expected = "https://youtu.be/xFnfOdb35FI and https://d.tnktok.com/t/ZTYXjHYeg/ but https://www.Example.com/Page";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_regex_budget() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut processor = create_processor()?;
+        processor.regex_budget = Duration::ZERO;
+        let message =
+            "https://www.tiktok.com/t/ZTYXjHYeg/ and https://youtube.com/shorts/xFnfOdb35FI";
+
+        let report = processor.report_message(message).await?;
+        assert_eq!(report.links.len(), 1);
+        assert!(
+            matches!(&report.links[0].error, Some(ReplaceError::RegexBudget(name)) if name == "tiktok")
+        );
+        assert!(report.reply().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_unsafe_regex() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = ReplacerConfig::new();
+        let mut unsafe_replacer = LinkReplacerConfig::new("fixed.example.com".into());
+        unsafe_replacer
+            .set_regex(r"https?://example\.com/(\w+/?)+\b".into())
+            .set_domain_re(r"example\.com".into())
+            .set_strip_query(false);
+        config.insert("unsafe".into(), unsafe_replacer);
+        let mut unsafe_tiktok = LinkReplacerConfig::new("d.tnktok.com".into());
+        unsafe_tiktok.set_regex(r"https?://tiktok\.com/(t/\w*)*(?=\s|$)".into());
+        config.insert("tiktok".into(), unsafe_tiktok);
+        let mut subdomains = LinkReplacerConfig::new("fixed.example.org".into());
+        subdomains
+            .set_regex(r"https?://([\w-]+\.)+example\.org/[^\s]+".into())
+            .set_domain_re(r"([\w-]+\.)+example\.org".into())
+            .set_strip_query(false);
+        config.insert("subdomains".into(), subdomains);
+        config.insert("youtube".into(), LinkReplacerConfig::new("youtu.be".into()));
+
+        let processor = create_processor_from(&config);
+        let mut names = replacer_names(&processor)[2..].to_vec();
+        names.sort();
+        assert_eq!(names, ["subdomains", "youtube"]);
+
+        let processor = MessageProcessor::new(
+            &ReplacerConfig::new(),
+            &AmazonConfig {
+                regex: r"https?://amazon\.com/(\w+/?)+(?<asin>\w+)\b".into(),
+                ..Default::default()
+            },
+            &FediverseConfig {
+                regex: r"https?://([\w.-]+\.?)+/@\w+/\d+(?!\d)".into(),
+                instances: vec!["mastodon.social".into()],
+                ..Default::default()
+            },
+            &UnwrapConfig::default(),
+            &ResolverConfig::default(),
+            &ProcessingConfig::default(),
+        );
+        let problems: Vec<_> = processor.problems().iter().map(|(name, _)| name).collect();
+        assert_eq!(problems, ["amazon", "fediverse"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builtin_backtrack_limit() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = ReplacerConfig::new();
+        // Backreferences run on the backtracking engine
+        let mut tiktok = LinkReplacerConfig::new("d.tnktok.com".into());
+        tiktok.set_regex(r"https?://tiktok\.com/(\w+)\1x".into());
+        config.insert("tiktok".into(), tiktok);
+        let processor = MessageProcessor::new(
+            &config,
+            &AmazonConfig::default(),
+            &FediverseConfig::default(),
+            &UnwrapConfig::default(),
+            &ResolverConfig::default(),
+            &ProcessingConfig {
+                regex_backtrack_limit: 100,
+                ..Default::default()
+            },
+        );
+        let message = format!("https://tiktok.com/{}", "a".repeat(100));
+
+        let report = processor.report_message(&message).await?;
+        assert!(matches!(
+            report.links[0].error,
+            Some(ReplaceError::Config(_))
+        ));
+        assert!(report.reply().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bare_links() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut processor = create_processor()?;
+        let message =
+            "Bare www.tiktok.com/t/ZTYXjHYeg/ and youtube.com/shorts/xFnfOdb35FI, not config.yaml";
+        let expected = "Bare https://d.tnktok.com/t/ZTYXjHYeg/ and https://youtu.be/xFnfOdb35FI, not config.yaml";
+
+        let result = processor.process_message(message).await?;
+        assert_eq!(result.as_deref(), Some(expected));
+
+        let resolved = HashMap::from([(
+            "https://vm.tiktok.com/ZMhvqKJ4B/".to_owned(),
+            "https://www.tiktok.com/t/ZTYXjHYeg/".to_owned(),
+        )]);
+        let deadline = Instant::now() + processor.message_timeout;
+        let result = processor
+            .process_resolved_message("vm.tiktok.com/ZMhvqKJ4B/", &resolved, deadline)
+            .await?
+            .reply();
+        assert_eq!(result.as_deref(), Some("https://d.tnktok.com/t/ZTYXjHYeg/"));
+
+        processor.fix_bare_links = false;
+        let report = processor.report_message(message).await?;
+        assert!(report.links.is_empty());
+        assert!(report.reply().is_none());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_unknown_link_types() -> ReplaceResult<()> {
         let processor = create_processor()?;
         let message = "Test message with [unknown link](https://example.com/v/Fsd6ZMcG0XN6OmOK) does not result in an error";
 
-        let result = processor.process_message(message);
+        let result = processor.process_message(message).await;
         assert!(result.is_ok());
         let result = result.unwrap();
         assert!(result.is_none());
@@ -0,0 +1,171 @@
+use super::{
+    build_regex, BacktrackLimit, LinkReplacer, LinkReplacerConfig, ReplaceConfigError,
+    ReplaceConfigResult, ReplaceMode, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct MusicReplacer {
+    config: MusicConfig,
+}
+
+const MUSIC_NEW_DOMAIN: &str = "song.link";
+const MUSIC_LINK_RE_STR: &str = r"https?://(open\.spotify\.com/(intl-[\w-]+/)?(track|album|playlist)/\w+|music\.apple\.com/\w{2}/(album|playlist|song)/[^\s]+|(listen\.)?tidal\.com/(browse/)?(track|album|playlist)/[\w-]+|(www\.)?deezer\.com/(\w{2}/)?(track|album|playlist)/\d+|music\.youtube\.com/(watch|playlist)\?[^\s]*(v|list)=[\w-]+)[^\s]*";
+/// YouTube Music video and playlist ids and the Apple Music track in an album
+const MUSIC_KEPT_QUERY: &[&str] = &["v", "list", "i"];
+
+pub fn music_default_new_domain() -> String {
+    MUSIC_NEW_DOMAIN.to_owned()
+}
+
+pub fn music_default_link_re_str() -> String {
+    MUSIC_LINK_RE_STR.to_owned()
+}
+
+impl MusicReplacer {
+    pub fn new(config: MusicConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl LinkReplacer for MusicReplacer {
+    fn get_regex(&self) -> &Regex {
+        &self.config.regex
+    }
+
+    fn mode(&self) -> ReplaceMode {
+        ReplaceMode::Append
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming music URL...");
+        let mut url = Url::parse(url)?;
+        let kept: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(field, _)| MUSIC_KEPT_QUERY.contains(&field.as_ref()))
+            .map(|(field, value)| (field.into_owned(), value.into_owned()))
+            .collect();
+        url.set_query(None);
+        if !kept.is_empty() {
+            url.query_pairs_mut().extend_pairs(kept);
+        }
+        url.set_fragment(None);
+        // The universal link page takes the original link as its path
+        let new_url = format!(
+            "https://{new_domain}/{url}",
+            new_domain = self.config.new_domain
+        );
+        debug! {%new_url, "new url"};
+        Ok(new_url)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MusicConfig {
+    new_domain: String,
+    regex: Regex,
+}
+
+impl MusicConfig {
+    pub fn new(new_domain: String, regex: String) -> ReplaceConfigResult<Self> {
+        let regex = Regex::new(&regex)?;
+        Ok(Self { new_domain, regex })
+    }
+}
+
+impl BacktrackLimit for MusicConfig {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for MusicConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        let new_domain = value
+            .new_domain
+            .clone()
+            .unwrap_or(music_default_new_domain());
+        let regex = value.regex.clone().unwrap_or(music_default_link_re_str());
+        Self::new(new_domain, regex)
+    }
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        Self::new(music_default_new_domain(), music_default_link_re_str()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> MusicReplacer {
+        MusicReplacer::new(MusicConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url =
+            "https://open.spotify.com/intl-de/track/4cOdK2wGLETKBW3PvgPWqT?si=0123456789abcdef";
+        let expected =
+            "https://song.link/https://open.spotify.com/intl-de/track/4cOdK2wGLETKBW3PvgPWqT";
+
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_other_services() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://music.apple.com/us/album/never-gonna-give-you-up/1558533900?i=1558534271&ls",
+                "https://song.link/https://music.apple.com/us/album/never-gonna-give-you-up/1558533900?i=1558534271",
+            ),
+            (
+                "https://tidal.com/browse/track/1234567?u",
+                "https://song.link/https://tidal.com/browse/track/1234567",
+            ),
+            (
+                "https://www.deezer.com/en/album/302127",
+                "https://song.link/https://www.deezer.com/en/album/302127",
+            ),
+            (
+                "https://music.youtube.com/watch?v=lYBUbBu4W08&si=abc123",
+                "https://song.link/https://music.youtube.com/watch?v=lYBUbBu4W08",
+            ),
+            (
+                "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M",
+                "https://song.link/https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignore_non_music_urls() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        assert!(!test_replacer.is_match("https://open.spotify.com/user/spotify")?);
+        assert!(!test_replacer.is_match("https://www.youtube.com/watch?v=lYBUbBu4W08")?);
+        Ok(())
+    }
+}
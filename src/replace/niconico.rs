@@ -0,0 +1,170 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct NiconicoReplacer {
+    inner: LinkProcessor,
+}
+
+const NICONICO_NEW_DOMAIN: &str = "nicovideo.gay";
+const NICONICO_LINK_RE_STR: &str =
+    r"https?://(((www|sp)\.)?nicovideo\.jp/watch/|nico\.ms/)(sm|so|nm)\d+[^\s]*";
+const NICONICO_DOMAIN_RE_STR: &str = r"((www|sp)\.)?nicovideo\.jp|nico\.ms";
+/// Niconico timestamps are given with `from`
+const NICONICO_KEPT_QUERY: &[&str] = &["from"];
+
+pub fn niconico_default_new_domain() -> String {
+    NICONICO_NEW_DOMAIN.to_owned()
+}
+
+pub fn niconico_default_link_re_str() -> &'static str {
+    NICONICO_LINK_RE_STR
+}
+
+pub fn niconico_default_domain_re_str() -> &'static str {
+    NICONICO_DOMAIN_RE_STR
+}
+
+pub fn niconico_default_strip_query() -> bool {
+    true
+}
+
+impl NiconicoReplacer {
+    pub fn new(config: NiconicoConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for NiconicoReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Niconico URL...");
+        let mut url = Url::parse(url)?;
+        // nico.ms/<id> is the short form of nicovideo.jp/watch/<id>
+        if url.host_str() == Some("nico.ms") {
+            let path = format!("/watch{}", url.path());
+            url.set_path(&path);
+        }
+        self.inner.transform_url(url.as_str())
+    }
+}
+
+pub struct NiconicoConfig {
+    inner: ProcessorConfig,
+}
+
+impl NiconicoConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?
+            .keep_query_params(NICONICO_KEPT_QUERY);
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<NiconicoConfig> for ProcessorConfig {
+    fn from(value: NiconicoConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for NiconicoConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for NiconicoConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(niconico_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(niconico_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(niconico_default_domain_re_str()),
+            value.strip_query.unwrap_or(niconico_default_strip_query()),
+        )
+    }
+}
+
+impl Default for NiconicoConfig {
+    fn default() -> Self {
+        Self::new(
+            niconico_default_new_domain(),
+            niconico_default_link_re_str(),
+            niconico_default_domain_re_str(),
+            niconico_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for NiconicoConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> NiconicoReplacer {
+        NiconicoReplacer::new(NiconicoConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.nicovideo.jp/watch/sm9?ref=pc_watch_description";
+        let expected = "https://nicovideo.gay/watch/sm9";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://sp.nicovideo.jp/watch/sm9?from=42&cmnhd_ref=device%3Dsp";
+        let expected = "https://nicovideo.gay/watch/sm9?from=42";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://nico.ms/sm9";
+        let expected = "https://nicovideo.gay/watch/sm9";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://nico.ms/so38016254?from=10";
+        let expected = "https://nicovideo.gay/watch/so38016254?from=10";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+}
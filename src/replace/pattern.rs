@@ -0,0 +1,173 @@
+use super::{LinkReplacerConfig, ReplaceConfigError, ReplaceConfigResult};
+use fancy_regex::{Assertion, Expr, Regex, RegexBuilder};
+
+/// Compiles a regex that gives up after `backtrack_limit` backtracking steps
+pub fn build_regex(pattern: &str, backtrack_limit: usize) -> ReplaceConfigResult<Regex> {
+    Ok(RegexBuilder::new(pattern)
+        .backtrack_limit(backtrack_limit)
+        .build()?)
+}
+
+/// Holds regexes that can be rebuilt to give up after a number of
+/// backtracking steps
+pub trait BacktrackLimit: Sized {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self>;
+}
+
+/// Whether the pattern continues with a quantifier that repeats without bound
+fn is_unbounded_quantifier(rest: &str) -> bool {
+    if rest.starts_with(['*', '+']) {
+        return true;
+    }
+    rest.strip_prefix('{')
+        .and_then(|rest| rest.split_once('}'))
+        .is_some_and(|(bounds, _)| {
+            bounds
+                .split_once(',')
+                .is_some_and(|(_, max)| max.is_empty())
+        })
+}
+
+/// Whether fancy_regex runs the expression on its backtracking VM. It does
+/// for lookaround, backreferences and word boundaries, everything else goes
+/// to the regex crate which matches in linear time
+fn needs_backtracking(expr: &Expr) -> bool {
+    match expr {
+        Expr::Empty | Expr::Any { .. } | Expr::Literal { .. } | Expr::Delegate { .. } => false,
+        Expr::Assertion(assertion) => matches!(
+            assertion,
+            Assertion::LeftWordBoundary
+                | Assertion::RightWordBoundary
+                | Assertion::WordBoundary
+                | Assertion::NotWordBoundary
+        ),
+        Expr::Concat(children) | Expr::Alt(children) => children.iter().any(needs_backtracking),
+        Expr::Group(child) | Expr::Repeat { child, .. } => needs_backtracking(child),
+        _ => true,
+    }
+}
+
+/// Rejects patterns with a repeated group that itself repeats without bound,
+/// such as `(a+)+` or `(\w*\s?)*`, which can backtrack exponentially. Only
+/// patterns that run on the backtracking VM are checked
+pub fn check_pattern(pattern: &str) -> ReplaceConfigResult<()> {
+    if !needs_backtracking(&Expr::parse_tree(pattern)?.expr) {
+        return Ok(());
+    }
+    // Whether each open group repeats something without bound
+    let mut groups: Vec<(usize, bool)> = Vec::new();
+    let mut repeats = false;
+    let mut chars = pattern.char_indices();
+    while let Some((position, c)) = chars.next() {
+        // Where the atom starting here ends
+        let end = match c {
+            '\\' => chars
+                .next()
+                .map_or(pattern.len(), |(i, c)| i + c.len_utf8()),
+            '[' => {
+                // A leading `]` is part of the class
+                let mut first = true;
+                let mut end = pattern.len();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        ']' if !first => {
+                            end = i + 1;
+                            break;
+                        }
+                        _ => (),
+                    }
+                    first = c == '^' && first;
+                }
+                end
+            }
+            '(' => {
+                groups.push((position, repeats));
+                repeats = false;
+                continue;
+            }
+            ')' => {
+                let (start, outer_repeats) = groups.pop().ok_or_else(|| {
+                    ReplaceConfigError::InvalidOption(format!("unbalanced `)` in {pattern}"))
+                })?;
+                let rest = &pattern[position + 1..];
+                if repeats && is_unbounded_quantifier(rest) {
+                    let group = &pattern[start..=position];
+                    return Err(ReplaceConfigError::UnsafeRegex(format!(
+                        "nested quantifier {group} in {pattern}"
+                    )));
+                }
+                repeats = outer_repeats || repeats || is_unbounded_quantifier(rest);
+                continue;
+            }
+            _ => position + c.len_utf8(),
+        };
+        repeats |= is_unbounded_quantifier(&pattern[end..]);
+    }
+    Ok(())
+}
+
+/// Checks every regex a replacer config supplies
+pub fn check_patterns(config: &LinkReplacerConfig) -> ReplaceConfigResult<()> {
+    [config.regex.as_deref(), config.domain_re.as_deref()]
+        .into_iter()
+        .flatten()
+        .try_for_each(check_pattern)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    #[tokio::test]
+    async fn test_check_pattern() {
+        init_tests().await;
+        let unsafe_patterns = [
+            r"(a+)+(?=b)",
+            r"https?://site\.com/(\w*\s?)*\b",
+            r"((ab)*c)+\2",
+            r"(x+x+){2,}(?<!z)y",
+            r"https?://(([\w)]+\.)+)*com(?!/)",
+        ];
+        for pattern in unsafe_patterns {
+            assert!(
+                matches!(
+                    check_pattern(pattern),
+                    Err(ReplaceConfigError::UnsafeRegex(_))
+                ),
+                "{pattern}"
+            );
+        }
+        let safe_patterns = [
+            r"https?://(www\.)?site0\.com/[^\s]+",
+            r"https?://(x|twitter)\.com/(\w){1,15}/status/[^\s]+",
+            r"https?://((www|m)\.)?facebook\.com/(watch/?\?([^\s]*&)?v=\d+)[^\s]*",
+            r"(\w+\.)?tiktok\.com",
+            r"[(a+)+]+(?=b)",
+            r"\(a+\)+\b",
+            // The regex crate matches these in linear time
+            r"(a+)+b",
+            r"([\w-]+\.)+example\.com",
+        ];
+        for pattern in safe_patterns {
+            assert!(check_pattern(pattern).is_ok(), "{pattern}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backtrack_limit() -> ReplaceConfigResult<()> {
+        init_tests().await;
+        // Backreferences run on the backtracking engine
+        let regex = build_regex(r"(\w+)\1x", 100)?;
+        let haystack = "a".repeat(100);
+        assert!(regex.find(&haystack).is_err());
+
+        let regex = build_regex(r"(\w+)\1x", 1_000_000)?;
+        assert!(regex.find(&haystack).is_ok());
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceError, ReplaceResult,
 };
 use fancy_regex::Regex;
 use tracing::{debug, instrument};
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub struct PixivReplacer {
@@ -11,7 +12,7 @@ pub struct PixivReplacer {
 }
 
 const PIXIV_NEW_DOMAIN: &str = "phixiv.net";
-const PIXIV_LINK_RE_STR: &str = r"https?://(\w+\.)?pixiv\.net/(\w+/)?(artworks|member_illust\.php)(/|\?illust_id=)\d+(/?\d+)?[^\s]+";
+const PIXIV_LINK_RE_STR: &str = r"https?://(\w+\.)?pixiv\.net/(([a-z]{2}/)?(artworks/\d+|novel/show\.php\?([^\s]*&)?id=\d+)|i/\d+|member_illust\.php\?([^\s]*&)?illust_id=\d+)[^\s]*";
 const PIXIV_DOMAIN_RE_STR: &str = r"(\w+\.)?(pixiv\.net)";
 
 pub fn pixiv_default_new_domain() -> String {
@@ -45,7 +46,57 @@ impl LinkReplacer for PixivReplacer {
     #[instrument(skip(self))]
     fn transform_url(&self, url: &str) -> ReplaceResult<String> {
         debug!("Transforming Pixiv URL...");
-        self.inner.transform_url(url)
+        let mut url = Url::parse(url)?;
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|s| s.map(str::to_owned).collect())
+            .unwrap_or_default();
+        let (lang, segments) = match segments.as_slice() {
+            [lang, rest @ ..] if lang.len() == 2 && !rest.is_empty() => (format!("/{lang}"), rest),
+            segments => (String::new(), segments),
+        };
+        // phixiv selects the page of a multi-page artwork with a 1-based path segment
+        let fragment_page = url.fragment().and_then(|f| f.parse::<u32>().ok());
+        url.set_fragment(None);
+        match segments {
+            [artworks, id] if artworks == "artworks" => {
+                let path = match fragment_page {
+                    Some(page) => format!("{lang}/artworks/{id}/{page}"),
+                    None => format!("{lang}/artworks/{id}"),
+                };
+                url.set_path(&path);
+            }
+            [i, id] if i == "i" => {
+                url.set_path(&format!("/artworks/{id}"));
+                url.set_query(None);
+            }
+            [member_illust] if member_illust == "member_illust.php" => {
+                let query_value = |field: &str| {
+                    url.query_pairs()
+                        .find(|(f, _)| f == field)
+                        .map(|(_, value)| value.into_owned())
+                };
+                let id = query_value("illust_id").ok_or(ReplaceError::NoQueryParams)?;
+                // member_illust.php pages are 0-based
+                let page = query_value("page").and_then(|p| p.parse::<u32>().ok());
+                let path = match page {
+                    Some(page) => format!("{lang}/artworks/{id}/{}", page + 1),
+                    None => format!("{lang}/artworks/{id}"),
+                };
+                url.set_path(&path);
+                url.set_query(None);
+            }
+            [novel, show] if novel == "novel" && show == "show.php" => {
+                let id = url
+                    .query_pairs()
+                    .find(|(f, _)| f == "id")
+                    .map(|(_, value)| value.into_owned())
+                    .ok_or(ReplaceError::NoQueryParams)?;
+                url.set_query(Some(&format!("id={id}")));
+            }
+            _ => {}
+        };
+        self.inner.transform_url(url.as_str())
     }
 }
 
@@ -71,6 +122,13 @@ impl From<PixivConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for PixivConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for PixivConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -120,8 +178,73 @@ mod test {
         let url = "https://www.pixiv.net/en/artworks/125183260";
         let expected = "https://phixiv.net/en/artworks/125183260";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_transform_page_index() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://www.pixiv.net/en/artworks/125183260/3",
+                "https://phixiv.net/en/artworks/125183260/3",
+            ),
+            (
+                "https://www.pixiv.net/artworks/125183260#2",
+                "https://phixiv.net/artworks/125183260/2",
+            ),
+            (
+                "https://www.pixiv.net/member_illust.php?mode=manga_big&illust_id=125183260&page=1",
+                "https://phixiv.net/artworks/125183260/2",
+            ),
+            (
+                "https://www.pixiv.net/member_illust.php?mode=medium&illust_id=125183260",
+                "https://phixiv.net/artworks/125183260",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_short_and_novel_urls() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://pixiv.net/i/125183260",
+                "https://phixiv.net/artworks/125183260",
+            ),
+            (
+                "https://www.pixiv.net/novel/show.php?id=21773470&utm_source=share",
+                "https://phixiv.net/novel/show.php?id=21773470",
+            ),
+            (
+                "https://www.pixiv.net/en/novel/show.php?id=21773470",
+                "https://phixiv.net/en/novel/show.php?id=21773470",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignore_user_urls() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        assert!(!test_replacer.is_match("https://www.pixiv.net/users/1234567")?);
+        assert!(!test_replacer.is_match("https://www.pixiv.net/en/users/1234567/illustrations")?);
+        Ok(())
+    }
 }
@@ -0,0 +1,51 @@
+use super::{MessageProcessor, MessageReport, ReplaceError, ReplaceResult};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::spawn_blocking;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// Runs the regex and transform work of a message on blocking threads so it
+/// never stalls the gateway tasks, while short links and replacers that wait
+/// on the network stay on the async task. Messages wait for a free worker
+/// when all of them are busy
+#[derive(Debug, Clone)]
+pub struct ProcessingPool {
+    workers: Arc<Semaphore>,
+}
+
+impl ProcessingPool {
+    pub fn new(workers: usize) -> Self {
+        let workers = Arc::new(Semaphore::new(workers.max(1)));
+        Self { workers }
+    }
+
+    pub async fn report_message(
+        &self,
+        processor: Arc<MessageProcessor>,
+        message: String,
+    ) -> ReplaceResult<MessageReport> {
+        let deadline = Instant::now() + processor.message_timeout;
+        let resolved = processor.resolve_links(&message, deadline).await;
+        if self.workers.available_permits() == 0 {
+            debug!("all workers are busy, waiting...");
+        }
+        let permit = self
+            .workers
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ReplaceError::Worker)?;
+        let (processor, message, spans) = spawn_blocking(move || {
+            let _permit = permit;
+            let spans = processor.match_links(&message, &resolved);
+            (processor, message, spans)
+        })
+        .await
+        .map_err(|err| {
+            warn! {%err, "message processing task failed"};
+            ReplaceError::Worker
+        })?;
+        Ok(processor.fix_links(&message, spans?, deadline).await)
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
 };
 use fancy_regex::Regex;
 use tracing::{debug, instrument};
@@ -72,6 +72,13 @@ impl From<RedditConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for RedditConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for RedditConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -128,12 +135,12 @@ mod test {
         let url = "https://www.reddit.com/r/Unexpected/comments/1hivblz/pro_tip_for_girls_to_get_home_safely_at_night/";
         let expected = "https://vxreddit.com/r/Unexpected/comments/1hivblz/pro_tip_for_girls_to_get_home_safely_at_night/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
 
         let url = "https://redd.it/6kq5hk";
         let expected = "https://vxreddit.com/6kq5hk";
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
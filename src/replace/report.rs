@@ -0,0 +1,80 @@
+use super::{ReplaceError, ReplaceMode};
+use std::ops::Range;
+
+/// What happened to a single link in a message
+#[derive(Debug, Clone)]
+pub struct LinkReport {
+    /// Where the link is in the message
+    pub span: Range<usize>,
+    pub original: String,
+    /// The link after resolving, unwrapping and canonicalizing it
+    pub target: String,
+    /// The fixed link, only set when a replacer handled it
+    pub fixed: Option<String>,
+    /// Name of the replacer that handled the link
+    pub replacer: Option<String>,
+    pub mode: ReplaceMode,
+    pub error: Option<ReplaceError>,
+}
+
+/// What happened to every link in a message
+#[derive(Debug, Clone)]
+pub struct MessageReport {
+    message: String,
+    pub links: Vec<LinkReport>,
+}
+
+impl MessageReport {
+    pub fn new(message: &str, links: Vec<LinkReport>) -> Self {
+        let message = message.to_owned();
+        Self { message, links }
+    }
+
+    /// Links a replacer fixed
+    pub fn fixed(&self) -> impl Iterator<Item = &LinkReport> {
+        self.links.iter().filter(|link| link.fixed.is_some())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &LinkReport> {
+        self.links.iter().filter(|link| link.error.is_some())
+    }
+
+    /// Whether the original message should lose its embeds. Appended links
+    /// leave the original link in place, so its embed is still wanted
+    pub fn suppress_embeds(&self) -> bool {
+        self.links
+            .iter()
+            .any(|link| link.fixed.is_some() && link.mode == ReplaceMode::Replace)
+    }
+
+    /// The message with its links fixed followed by the appended links, or
+    /// just the appended links when nothing in the message changed. `None`
+    /// if there is nothing to reply with. Links that failed are left as they
+    /// are
+    pub fn reply(&self) -> Option<String> {
+        let mut reply = String::with_capacity(self.message.len());
+        let mut appended = Vec::new();
+        let mut last_end = 0;
+        for link in &self.links {
+            reply.push_str(&self.message[last_end..link.span.start]);
+            last_end = link.span.end;
+            match (&link.fixed, link.mode) {
+                (Some(fixed), ReplaceMode::Append) => {
+                    appended.push(fixed.as_str());
+                    reply.push_str(&link.original);
+                }
+                (Some(fixed), ReplaceMode::Replace) => reply.push_str(fixed),
+                (None, _) => reply.push_str(&link.original),
+            }
+        }
+        reply.push_str(&self.message[last_end..]);
+        if reply == self.message {
+            return (!appended.is_empty()).then(|| appended.join("\n"));
+        }
+        for appended_url in appended {
+            reply.push('\n');
+            reply.push_str(appended_url);
+        }
+        Some(reply)
+    }
+}
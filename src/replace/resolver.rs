@@ -0,0 +1,362 @@
+use super::{ReplaceConfigError, ReplaceConfigResult};
+use reqwest::{header::LOCATION, redirect::Policy, Client};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument, warn};
+use url::Url;
+
+fn resolver_default_enabled() -> bool {
+    false
+}
+
+fn resolver_default_hosts() -> Vec<String> {
+    [
+        "t.co",
+        "vm.tiktok.com",
+        "vt.tiktok.com",
+        "reddit.com/r/*/s/",
+        "b23.tv",
+        "a.co",
+        "amzn.to",
+        "fb.watch",
+    ]
+    .iter()
+    .map(|host| host.to_string())
+    .collect()
+}
+
+fn resolver_default_timeout_ms() -> u64 {
+    2000
+}
+
+fn resolver_default_max_redirects() -> usize {
+    5
+}
+
+fn resolver_default_cache_ttl_secs() -> u64 {
+    3600
+}
+
+fn resolver_default_cache_size() -> usize {
+    1024
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolverConfig {
+    #[serde(default = "resolver_default_enabled")]
+    pub enabled: bool,
+    /// Short-link hosts that may be resolved, optionally followed by a path
+    /// prefix where `*` matches a single segment (i.e. `reddit.com/r/*/s/`)
+    #[serde(default = "resolver_default_hosts")]
+    pub hosts: Vec<String>,
+    #[serde(default = "resolver_default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "resolver_default_max_redirects")]
+    pub max_redirects: usize,
+    #[serde(default = "resolver_default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    #[serde(default = "resolver_default_cache_size")]
+    pub cache_size: usize,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: resolver_default_enabled(),
+            hosts: resolver_default_hosts(),
+            timeout_ms: resolver_default_timeout_ms(),
+            max_redirects: resolver_default_max_redirects(),
+            cache_ttl_secs: resolver_default_cache_ttl_secs(),
+            cache_size: resolver_default_cache_size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AllowedLink {
+    host: String,
+    path: Vec<String>,
+}
+
+impl AllowedLink {
+    fn new(entry: &str) -> Self {
+        let (host, path) = entry.split_once('/').unwrap_or((entry, ""));
+        let host = host.strip_prefix("www.").unwrap_or(host).to_lowercase();
+        let path = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Self { host, path }
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let host = url.host_str().unwrap_or_default();
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let mut segments = url.path_segments().into_iter().flatten();
+        host == self.host
+            && self.path.iter().all(|expected| {
+                segments
+                    .next()
+                    .is_some_and(|s| expected == "*" || s == expected)
+            })
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    inserted: Instant,
+    resolved: String,
+}
+
+/// Follows the redirects of short links to find the link they point to
+#[derive(Debug)]
+pub struct LinkResolver {
+    client: Client,
+    allowlist: Vec<AllowedLink>,
+    max_redirects: usize,
+    cache_ttl: Duration,
+    cache_size: usize,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl LinkResolver {
+    pub fn new(config: &ResolverConfig) -> ReplaceConfigResult<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let timeout = Duration::from_millis(config.timeout_ms);
+        // Redirects are followed by hand so only allowlisted hosts are ever requested
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .build()
+            .map_err(|err| ReplaceConfigError::InvalidOption(err.to_string()))?;
+        let allowlist = config.hosts.iter().map(|h| AllowedLink::new(h)).collect();
+        Ok(Some(Self {
+            client,
+            allowlist,
+            max_redirects: config.max_redirects,
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+            cache_size: config.cache_size,
+            cache: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        matches!(url.scheme(), "http" | "https") && self.allowlist.iter().any(|a| a.matches(url))
+    }
+
+    /// Whether the link is a short link this resolver would look up
+    pub fn can_resolve(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| self.is_allowed(&url))
+    }
+
+    fn cached(&self, url: &str) -> Option<String> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .get(url)
+            .filter(|entry| entry.inserted.elapsed() < self.cache_ttl)
+            .map(|entry| entry.resolved.clone())
+    }
+
+    fn store(&self, url: &str, resolved: String) {
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= self.cache_size {
+                cache.retain(|_, entry| entry.inserted.elapsed() < self.cache_ttl);
+            }
+            if cache.len() >= self.cache_size {
+                if let Some(oldest) = cache
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.inserted)
+                    .map(|(url, _)| url.clone())
+                {
+                    cache.remove(&oldest);
+                }
+            }
+            let inserted = Instant::now();
+            cache.insert(url.to_owned(), CacheEntry { inserted, resolved });
+        }
+    }
+
+    async fn follow(&self, url: &Url) -> Option<String> {
+        let mut current = url.clone();
+        for _ in 0..self.max_redirects {
+            let response = self
+                .client
+                .head(current.clone())
+                .send()
+                .await
+                .map_err(|err| warn! {%err, url = %current, "could not resolve short link"})
+                .ok()?;
+            if !response.status().is_redirection() {
+                break;
+            }
+            let location = response.headers().get(LOCATION)?.to_str().ok()?;
+            let next = current.join(location).ok()?;
+            debug! {%current, %next, "followed redirect"};
+            if !self.is_allowed(&next) {
+                // Stop at the first link that isn't another short link
+                return matches!(next.scheme(), "http" | "https").then(|| next.to_string());
+            }
+            current = next;
+        }
+        // Out of redirects while still on short links
+        None
+    }
+
+    /// Returns the link a short link redirects to, or `None` if it can't be
+    /// resolved. Only resolved links are cached so a failure is retried
+    #[instrument(skip(self))]
+    pub async fn resolve(&self, url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok().filter(|url| self.is_allowed(url))?;
+        if let Some(resolved) = self.cached(url) {
+            debug!("short link cache hit");
+            return Some(resolved);
+        }
+        let resolved = self.follow(&parsed).await?;
+        self.store(url, resolved.clone());
+        Some(resolved)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{init_tests, spawn_redirect_server};
+    use std::sync::atomic::Ordering;
+
+    fn create_test_resolver(timeout_ms: u64) -> LinkResolver {
+        LinkResolver::new(&ResolverConfig {
+            enabled: true,
+            hosts: vec!["127.0.0.1".into()],
+            timeout_ms,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_short_link() {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let resolver = create_test_resolver(2000);
+
+        let resolved = resolver
+            .resolve(&format!("{}/s/tiktok", server.address))
+            .await;
+        assert_eq!(
+            resolved.as_deref(),
+            Some("https://www.tiktok.com/t/ZTYXjHYeg/")
+        );
+
+        // Relative redirects between short links are followed
+        let resolved = resolver.resolve(&format!("{}/hop", server.address)).await;
+        assert_eq!(
+            resolved.as_deref(),
+            Some("https://www.tiktok.com/t/ZTYXjHYeg/")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_cache() {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let resolver = create_test_resolver(2000);
+        let url = format!("{}/s/tiktok", server.address);
+
+        resolver.resolve(&url).await;
+        resolver.resolve(&url).await;
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+
+        let expired_resolver = LinkResolver::new(&ResolverConfig {
+            enabled: true,
+            hosts: vec!["127.0.0.1".into()],
+            cache_ttl_secs: 0,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap();
+        expired_resolver.resolve(&url).await;
+        expired_resolver.resolve(&url).await;
+        assert_eq!(server.hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_after_failure() {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let resolver = create_test_resolver(2000);
+        let url = format!("{}/flaky", server.address);
+
+        // The first request fails, which isn't remembered
+        assert!(resolver.resolve(&url).await.is_none());
+        assert_eq!(
+            resolver.resolve(&url).await.as_deref(),
+            Some("https://www.tiktok.com/t/ZTYXjHYeg/")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_out_of_redirects() {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let resolver = LinkResolver::new(&ResolverConfig {
+            enabled: true,
+            hosts: vec!["127.0.0.1".into()],
+            max_redirects: 1,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap();
+
+        // /hop only gets as far as another short link
+        assert!(resolver
+            .resolve(&format!("{}/hop", server.address))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_timeout_and_allowlist() {
+        init_tests().await;
+        let server = spawn_redirect_server().await;
+        let resolver = create_test_resolver(100);
+
+        assert!(resolver
+            .resolve(&format!("{}/slow", server.address))
+            .await
+            .is_none());
+        assert!(resolver
+            .resolve("https://example.com/s/tiktok")
+            .await
+            .is_none());
+        assert!(resolver
+            .resolve(&format!("{}/not-a-redirect", server.address))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_paths() {
+        init_tests().await;
+        let resolver = LinkResolver::new(&ResolverConfig {
+            enabled: true,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap();
+        assert!(resolver.can_resolve("https://www.reddit.com/r/rust/s/AbCdEf123"));
+        assert!(!resolver.can_resolve("https://www.reddit.com/r/rust/comments/abc/title/"));
+        assert!(resolver.can_resolve("https://t.co/AbCdEf123"));
+        assert!(!resolver.can_resolve("https://x.com/user/status/1"));
+        assert!(LinkResolver::new(&ResolverConfig::default())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use super::{
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+
+#[derive(Debug, Clone)]
+pub struct ThreadsReplacer {
+    inner: LinkProcessor,
+}
+
+const THREADS_NEW_DOMAIN: &str = "fixthreads.net";
+const THREADS_LINK_RE_STR: &str = r"https?://(www\.)?threads\.(net|com)/@[\w.]+/post/[\w-]+[^\s]*";
+const THREADS_DOMAIN_RE_STR: &str = r"(www\.)?threads\.(net|com)";
+
+pub fn threads_default_new_domain() -> String {
+    THREADS_NEW_DOMAIN.to_owned()
+}
+
+pub fn threads_default_link_re_str() -> &'static str {
+    THREADS_LINK_RE_STR
+}
+
+pub fn threads_default_domain_re_str() -> &'static str {
+    THREADS_DOMAIN_RE_STR
+}
+
+pub fn threads_default_strip_query() -> bool {
+    true
+}
+
+impl ThreadsReplacer {
+    pub fn new(config: ThreadsConfig) -> Self {
+        let inner = LinkProcessor::new(config.into());
+        Self { inner }
+    }
+}
+
+impl LinkReplacer for ThreadsReplacer {
+    fn get_regex(&self) -> &Regex {
+        self.inner.get_regex()
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Threads URL...");
+        self.inner.transform_url(url)
+    }
+}
+
+pub struct ThreadsConfig {
+    inner: ProcessorConfig,
+}
+
+impl ThreadsConfig {
+    pub fn new(
+        new_domain: String,
+        regex: &str,
+        domain_regex: &str,
+        strip_query: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let config = ProcessorConfig::new(new_domain, regex, domain_regex, strip_query)?;
+        Ok(Self { inner: config })
+    }
+}
+
+impl From<ThreadsConfig> for ProcessorConfig {
+    fn from(value: ThreadsConfig) -> Self {
+        value.inner
+    }
+}
+
+impl BacktrackLimit for ThreadsConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for ThreadsConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .new_domain
+                .clone()
+                .unwrap_or(threads_default_new_domain()),
+            value
+                .regex
+                .as_deref()
+                .unwrap_or(threads_default_link_re_str()),
+            value
+                .domain_re
+                .as_deref()
+                .unwrap_or(threads_default_domain_re_str()),
+            value.strip_query.unwrap_or(threads_default_strip_query()),
+        )
+    }
+}
+
+impl Default for ThreadsConfig {
+    fn default() -> Self {
+        Self::new(
+            threads_default_new_domain(),
+            threads_default_link_re_str(),
+            threads_default_domain_re_str(),
+            threads_default_strip_query(),
+        )
+        .unwrap()
+    }
+}
+
+impl AsRef<ProcessorConfig> for ThreadsConfig {
+    fn as_ref(&self) -> &ProcessorConfig {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> ThreadsReplacer {
+        ThreadsReplacer::new(ThreadsConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://www.threads.net/@zuck/post/C8Hf4qqv8Ju?xmt=AQGzKx1";
+        let expected = "https://fixthreads.net/@zuck/post/C8Hf4qqv8Ju";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.threads.com/@some.user_1/post/DBdI8rnSb-0";
+        let expected = "https://fixthreads.net/@some.user_1/post/DBdI8rnSb-0";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+}
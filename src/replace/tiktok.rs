@@ -1,9 +1,10 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
 };
 use fancy_regex::Regex;
 use tracing::{debug, instrument};
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub struct TikTokReplacer {
@@ -11,8 +12,10 @@ pub struct TikTokReplacer {
 }
 
 const TIKTOK_NEW_DOMAIN: &str = "d.tnktok.com";
-const TIKTOK_LINK_RE_STR: &str = r"https?://(\w+\.)?tiktok\.com/((t/)?\w+|@[^\s]+/video)";
-const TIKTOK_DOMAIN_RE_STR: &str = r"([w]{3}\.)?tiktok\.com";
+const TIKTOK_LINK_RE_STR: &str = r"https?://((vm|vt)\.tiktok\.com/\w+|((www|m)\.)?tiktok\.com/(t/\w+|@[\w.-]+/((video|photo)/\d+|live\b)|v/\d+\.html))[^\s]*";
+const TIKTOK_DOMAIN_RE_STR: &str = r"(\w+\.)?tiktok\.com";
+/// Hosts that only serve short links
+const TIKTOK_SHORT_HOSTS: &[&str] = &["vm.tiktok.com", "vt.tiktok.com"];
 
 pub fn tiktok_default_new_domain() -> String {
     TIKTOK_NEW_DOMAIN.to_owned()
@@ -45,7 +48,28 @@ impl LinkReplacer for TikTokReplacer {
     #[instrument(skip(self))]
     fn transform_url(&self, url: &str) -> ReplaceResult<String> {
         debug!("Transforming TikTok URL...");
-        self.inner.transform_url(url)
+        let mut url = Url::parse(url)?;
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|s| s.map(str::to_owned).collect())
+            .unwrap_or_default();
+        let is_short_host = url
+            .host_str()
+            .is_some_and(|host| TIKTOK_SHORT_HOSTS.contains(&host));
+        match segments.as_slice() {
+            // vm.tiktok.com/<code> is the same short link as tiktok.com/t/<code>
+            [_, ..] if is_short_host => {
+                url.set_path(&format!("/t/{}", segments.join("/")));
+            }
+            // m.tiktok.com/v/<id>.html doesn't name the user, TikTok itself
+            // sends these to /@/video/<id>
+            [v, video] if v == "v" => {
+                let video_id = video.trim_end_matches(".html");
+                url.set_path(&format!("/@/video/{video_id}"));
+            }
+            _ => {}
+        };
+        self.inner.transform_url(url.as_str())
     }
 }
 
@@ -71,6 +95,13 @@ impl From<TikTokConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for TikTokConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for TikTokConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -127,8 +158,70 @@ mod test {
         let url = "https://www.tiktok.com/t/ZTYXjHYeg/";
         let expected = "https://d.tnktok.com/t/ZTYXjHYeg/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_transform_video_photo_and_live_urls() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://www.tiktok.com/@some.user/video/7345678901234567890?is_from_webapp=1&sender_device=pc",
+                "https://d.tnktok.com/@some.user/video/7345678901234567890",
+            ),
+            (
+                "https://www.tiktok.com/@some.user/photo/7345678901234567890",
+                "https://d.tnktok.com/@some.user/photo/7345678901234567890",
+            ),
+            (
+                "https://www.tiktok.com/@some.user/live?enter_from_merge=others_homepage",
+                "https://d.tnktok.com/@some.user/live",
+            ),
+            (
+                "https://m.tiktok.com/v/7345678901234567890.html?u_code=abc",
+                "https://d.tnktok.com/@/video/7345678901234567890",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transform_short_links() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let cases = [
+            (
+                "https://vm.tiktok.com/ZMhvqjqjX/",
+                "https://d.tnktok.com/t/ZMhvqjqjX/",
+            ),
+            (
+                "https://vt.tiktok.com/ZSjXyVNbP/",
+                "https://d.tnktok.com/t/ZSjXyVNbP/",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert!(test_replacer.is_match(url)?);
+            let result = test_replacer.transform_url(url)?;
+            assert_eq!(expected, result);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignore_non_post_urls() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        assert!(!test_replacer.is_match("https://www.tiktok.com/@some.user")?);
+        assert!(!test_replacer.is_match("https://www.tiktok.com/explore")?);
+        assert!(!test_replacer.is_match("https://www.tiktok.com/@some.user/liked")?);
+        Ok(())
+    }
 }
@@ -0,0 +1,202 @@
+use super::{
+    build_regex, BacktrackLimit, LinkReplacer, LinkReplacerConfig, ReplaceConfigError,
+    ReplaceConfigResult, ReplaceError, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct TumblrReplacer {
+    config: TumblrConfig,
+}
+
+const TUMBLR_NEW_DOMAIN: &str = "tpmblr.com";
+const TUMBLR_LINK_RE_STR: &str =
+    r"https?://((?!www\.)[\w-]+\.tumblr\.com/post/\d+|(www\.)?tumblr\.com/[\w-]+/\d+)[^\s]*";
+const TUMBLR_DROP_SLUG_OPTION: &str = "drop_slug";
+
+pub fn tumblr_default_new_domain() -> String {
+    TUMBLR_NEW_DOMAIN.to_owned()
+}
+
+pub fn tumblr_default_link_re_str() -> String {
+    TUMBLR_LINK_RE_STR.to_owned()
+}
+
+pub fn tumblr_default_strip_query() -> bool {
+    true
+}
+
+pub fn tumblr_default_drop_slug() -> bool {
+    false
+}
+
+impl TumblrReplacer {
+    pub fn new(config: TumblrConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl LinkReplacer for TumblrReplacer {
+    fn get_regex(&self) -> &Regex {
+        &self.config.regex
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Tumblr URL...");
+        let mut url = Url::parse(url)?;
+        let host = url.host_str().ok_or(ReplaceError::UrlHost)?.to_owned();
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|s| s.map(str::to_owned).collect())
+            .unwrap_or_default();
+        // Both <blog>.tumblr.com/post/<id>/<slug> and tumblr.com/<blog>/<id>/<slug>
+        // end up as <blog>/<id>/<slug> on the new domain
+        let (blog, post) = match host.strip_suffix(".tumblr.com") {
+            Some(blog) if blog != "www" => (blog.to_owned(), segments.get(1..)),
+            _ => (
+                segments.first().cloned().unwrap_or_default(),
+                segments.get(1..),
+            ),
+        };
+        let mut post = post.unwrap_or_default().iter().filter(|s| !s.is_empty());
+        let id = post
+            .next()
+            .ok_or(ReplaceError::UrlNotModified(url.to_string()))?;
+        let path = match post.next() {
+            Some(slug) if !self.config.drop_slug => format!("/{blog}/{id}/{slug}"),
+            _ => format!("/{blog}/{id}"),
+        };
+        url.set_host(Some(&self.config.new_domain))?;
+        url.set_path(&path);
+        if self.config.strip_query {
+            url.set_query(None);
+        }
+        let new_url = url.to_string();
+        debug! {%new_url, "new url"};
+        Ok(new_url)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TumblrConfig {
+    new_domain: String,
+    regex: Regex,
+    strip_query: bool,
+    drop_slug: bool,
+}
+
+impl TumblrConfig {
+    pub fn new(
+        new_domain: String,
+        regex: String,
+        strip_query: bool,
+        drop_slug: bool,
+    ) -> ReplaceConfigResult<Self> {
+        let regex = Regex::new(&regex)?;
+        Ok(Self {
+            new_domain,
+            regex,
+            strip_query,
+            drop_slug,
+        })
+    }
+}
+
+impl BacktrackLimit for TumblrConfig {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for TumblrConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        let new_domain = value
+            .new_domain
+            .clone()
+            .unwrap_or(tumblr_default_new_domain());
+        let regex = value.regex.clone().unwrap_or(tumblr_default_link_re_str());
+        let strip_query = value.strip_query.unwrap_or(tumblr_default_strip_query());
+        let drop_slug = value
+            .custom_config
+            .get(TUMBLR_DROP_SLUG_OPTION)
+            .map(|drop_slug| {
+                drop_slug
+                    .parse::<bool>()
+                    .map_err(|_| ReplaceConfigError::InvalidOption(drop_slug.to_owned()))
+            })
+            .transpose()?
+            .unwrap_or(tumblr_default_drop_slug());
+        Self::new(new_domain, regex, strip_query, drop_slug)
+    }
+}
+
+impl Default for TumblrConfig {
+    fn default() -> Self {
+        Self::new(
+            tumblr_default_new_domain(),
+            tumblr_default_link_re_str(),
+            tumblr_default_strip_query(),
+            tumblr_default_drop_slug(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> TumblrReplacer {
+        TumblrReplacer::new(TumblrConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://staff.tumblr.com/post/752395784163934208/new-features?source=share";
+        let expected = "https://tpmblr.com/staff/752395784163934208/new-features";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.tumblr.com/staff/752395784163934208/new-features";
+        let expected = "https://tpmblr.com/staff/752395784163934208/new-features";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://tumblr.com/some-blog/752395784163934208";
+        let expected = "https://tpmblr.com/some-blog/752395784163934208";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        assert!(!test_replacer.is_match("https://www.tumblr.com/tagged/art")?);
+        assert!(!test_replacer.is_match("https://staff.tumblr.com/")?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_drop_slug() -> ReplaceResult<()> {
+        init_tests().await;
+        let mut config = LinkReplacerConfig::default();
+        config
+            .custom_config
+            .insert("drop_slug".into(), "true".into());
+        let test_replacer = TumblrReplacer::new((&config).try_into()?);
+        let url = "https://staff.tumblr.com/post/752395784163934208/new-features";
+        let expected = "https://tpmblr.com/staff/752395784163934208";
+
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use super::{
+    build_regex, BacktrackLimit, LinkReplacer, LinkReplacerConfig, ReplaceConfigError,
+    ReplaceConfigResult, ReplaceError, ReplaceResult,
+};
+use fancy_regex::Regex;
+use tracing::{debug, instrument};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct TwitchReplacer {
+    config: TwitchConfig,
+}
+
+const TWITCH_NEW_DOMAIN: &str = "clips.fxtwitch.tv";
+const TWITCH_LINK_RE_STR: &str = r"https?://(clips\.twitch\.tv/(?!embed\b)[\w-]+|((www|m)\.)?twitch\.tv/(\w+/)?clip/[\w-]+)[^\s]*";
+
+pub fn twitch_default_new_domain() -> String {
+    TWITCH_NEW_DOMAIN.to_owned()
+}
+
+pub fn twitch_default_link_re_str() -> String {
+    TWITCH_LINK_RE_STR.to_owned()
+}
+
+impl TwitchReplacer {
+    pub fn new(config: TwitchConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl LinkReplacer for TwitchReplacer {
+    fn get_regex(&self) -> &Regex {
+        &self.config.regex
+    }
+
+    #[instrument(skip(self))]
+    fn transform_url(&self, url: &str) -> ReplaceResult<String> {
+        debug!("Transforming Twitch URL...");
+        let url = Url::parse(url)?;
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        // Every clip form ends in the clip slug: clips.twitch.tv/<slug>,
+        // twitch.tv/<channel>/clip/<slug> and m.twitch.tv/clip/<slug>
+        let slug = match segments.as_slice() {
+            [.., "clip", slug] => slug,
+            [slug] if url.host_str() == Some("clips.twitch.tv") => slug,
+            _ => return Err(ReplaceError::UrlNotModified(url.to_string())),
+        };
+        let new_url = format!(
+            "https://{new_domain}/{slug}",
+            new_domain = self.config.new_domain
+        );
+        debug! {%new_url, "new url"};
+        Ok(new_url)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TwitchConfig {
+    new_domain: String,
+    regex: Regex,
+}
+
+impl TwitchConfig {
+    pub fn new(new_domain: String, regex: String) -> ReplaceConfigResult<Self> {
+        let regex = Regex::new(&regex)?;
+        Ok(Self { new_domain, regex })
+    }
+}
+
+impl BacktrackLimit for TwitchConfig {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
+impl TryFrom<&LinkReplacerConfig> for TwitchConfig {
+    type Error = ReplaceConfigError;
+    fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
+        let new_domain = value
+            .new_domain
+            .clone()
+            .unwrap_or(twitch_default_new_domain());
+        let regex = value.regex.clone().unwrap_or(twitch_default_link_re_str());
+        Self::new(new_domain, regex)
+    }
+}
+
+impl Default for TwitchConfig {
+    fn default() -> Self {
+        Self::new(twitch_default_new_domain(), twitch_default_link_re_str()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_replacer() -> TwitchReplacer {
+        TwitchReplacer::new(TwitchConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_transform_url() -> ReplaceResult<()> {
+        init_tests().await;
+        let test_replacer = create_test_replacer();
+        let url = "https://clips.twitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        let expected = "https://clips.fxtwitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://www.twitch.tv/somechannel/clip/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H?filter=clips&range=7d&sort=time";
+        let expected = "https://clips.fxtwitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url =
+            "https://twitch.tv/somechannel/clip/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        let expected = "https://clips.fxtwitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://m.twitch.tv/clip/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        let expected = "https://clips.fxtwitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        let url = "https://m.twitch.tv/somechannel/clip/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H/";
+        let expected = "https://clips.fxtwitch.tv/AgreeableBeautifulKoalaDansGame-1a2B3c4D5e6F7g8H";
+        assert!(test_replacer.is_match(url)?);
+        let result = test_replacer.transform_url(url)?;
+        assert_eq!(expected, result);
+
+        assert!(!test_replacer.is_match("https://www.twitch.tv/somechannel")?);
+        assert!(!test_replacer.is_match("https://www.twitch.tv/videos/2312345678")?);
+        assert!(!test_replacer.is_match("https://m.twitch.tv/somechannel/clips")?);
+        Ok(())
+    }
+}
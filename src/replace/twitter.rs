@@ -1,6 +1,6 @@
 use super::{
-    LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig, ReplaceConfigError,
-    ReplaceConfigResult, ReplaceResult,
+    BacktrackLimit, LinkProcessor, LinkReplacer, LinkReplacerConfig, ProcessorConfig,
+    ReplaceConfigError, ReplaceConfigResult, ReplaceResult,
 };
 use fancy_regex::Regex;
 use tracing::{debug, instrument};
@@ -71,6 +71,13 @@ impl From<TwitterConfig> for ProcessorConfig {
     }
 }
 
+impl BacktrackLimit for TwitterConfig {
+    fn backtrack_limit(self, limit: usize) -> ReplaceConfigResult<Self> {
+        let inner = self.inner.backtrack_limit(limit)?;
+        Ok(Self { inner })
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for TwitterConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -127,7 +134,7 @@ mod test {
         let url = "https://x.com/PhillyD/status/1870093335936823564/";
         let expected = "https://fxtwitter.com/PhillyD/status/1870093335936823564/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -0,0 +1,304 @@
+use serde::Deserialize;
+use tracing::{debug, instrument};
+use url::Url;
+
+/// Where a wrapper keeps the link it points to
+#[derive(Debug, Clone, Copy)]
+enum WrapperTarget {
+    /// The first of these query params that is set
+    Param(&'static [&'static str]),
+    /// The rest of the path after the wrapper path, without a scheme, and
+    /// the query string
+    PathPrefix,
+    /// The whole query string
+    Query,
+    /// The `url` query param, with Reddit image previews swapped for the
+    /// full size image
+    RedditMedia,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Wrapper {
+    /// Hosts without `www.`, a trailing `.*` matches any TLD
+    hosts: &'static [&'static str],
+    /// An empty path matches any path
+    path: &'static str,
+    target: WrapperTarget,
+}
+
+const WRAPPERS: &[Wrapper] = &[
+    Wrapper {
+        hosts: &["google.*"],
+        path: "/url",
+        target: WrapperTarget::Param(&["q", "url"]),
+    },
+    Wrapper {
+        hosts: &["google.*"],
+        path: "/amp/s/",
+        target: WrapperTarget::PathPrefix,
+    },
+    Wrapper {
+        hosts: &["l.facebook.com", "lm.facebook.com"],
+        path: "/l.php",
+        target: WrapperTarget::Param(&["u"]),
+    },
+    Wrapper {
+        hosts: &["l.instagram.com"],
+        path: "/",
+        target: WrapperTarget::Param(&["u"]),
+    },
+    Wrapper {
+        hosts: &["out.reddit.com"],
+        path: "",
+        target: WrapperTarget::Param(&["url"]),
+    },
+    Wrapper {
+        hosts: &["reddit.com"],
+        path: "/media",
+        target: WrapperTarget::RedditMedia,
+    },
+    Wrapper {
+        hosts: &["href.li"],
+        path: "/",
+        target: WrapperTarget::Query,
+    },
+];
+
+/// Image types Reddit serves at full size on i.redd.it
+const REDDIT_IMAGE_TYPES: &[&str] = &[".jpeg", ".jpg", ".png", ".gif", ".webp"];
+
+/// Swaps a resized preview.redd.it image for the full size one on i.redd.it,
+/// other links are left as they are
+fn full_size_reddit_image(target: String) -> String {
+    let Ok(mut url) = Url::parse(&target) else {
+        return target;
+    };
+    let is_image = REDDIT_IMAGE_TYPES
+        .iter()
+        .any(|image_type| url.path().ends_with(image_type));
+    if !is_image || !matches!(url.host_str(), Some("preview.redd.it" | "i.redd.it")) {
+        return target;
+    }
+    if url.set_host(Some("i.redd.it")).is_err() {
+        return target;
+    }
+    url.set_query(None);
+    url.into()
+}
+
+fn query_param(url: &Url, params: &[&str]) -> Option<String> {
+    params.iter().find_map(|param| {
+        url.query_pairs()
+            .find(|(field, _)| field == param)
+            .map(|(_, value)| value.into_owned())
+    })
+}
+
+fn unwrap_default_enabled() -> bool {
+    true
+}
+
+fn unwrap_default_max_depth() -> usize {
+    3
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnwrapConfig {
+    #[serde(default = "unwrap_default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "unwrap_default_max_depth")]
+    pub max_depth: usize,
+}
+
+impl Default for UnwrapConfig {
+    fn default() -> Self {
+        let enabled = unwrap_default_enabled();
+        let max_depth = unwrap_default_max_depth();
+        Self { enabled, max_depth }
+    }
+}
+
+impl Wrapper {
+    fn matches_host(&self, host: &str) -> bool {
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        self.hosts
+            .iter()
+            .any(|pattern| match pattern.strip_suffix(".*") {
+                Some(name) => host
+                    .strip_prefix(name)
+                    .is_some_and(|tld| tld.starts_with('.')),
+                None => host == *pattern,
+            })
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        match self.target {
+            WrapperTarget::PathPrefix => path.starts_with(self.path),
+            _ if self.path.is_empty() => true,
+            _ => path == self.path || path == self.path.trim_end_matches('/'),
+        }
+    }
+
+    fn target(&self, url: &Url) -> Option<String> {
+        match self.target {
+            WrapperTarget::Param(params) => query_param(url, params),
+            WrapperTarget::PathPrefix => {
+                url.path()
+                    .strip_prefix(self.path)
+                    .map(|rest| match url.query() {
+                        Some(query) => format!("https://{rest}?{query}"),
+                        None => format!("https://{rest}"),
+                    })
+            }
+            WrapperTarget::Query => url
+                .query()
+                .and_then(|query| urlencoding::decode(query).ok())
+                .map(|query| query.into_owned()),
+            WrapperTarget::RedditMedia => query_param(url, &["url"]).map(full_size_reddit_image),
+        }
+    }
+}
+
+/// Extracts the real link from redirectors and AMP pages
+#[derive(Debug, Clone)]
+pub struct LinkUnwrapper {
+    max_depth: usize,
+}
+
+impl LinkUnwrapper {
+    pub fn new(config: &UnwrapConfig) -> Option<Self> {
+        config.enabled.then_some(Self {
+            max_depth: config.max_depth,
+        })
+    }
+
+    fn unwrap_once(url: &str) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        WRAPPERS
+            .iter()
+            .find(|wrapper| wrapper.matches_host(host) && wrapper.matches_path(url.path()))
+            .and_then(|wrapper| wrapper.target(&url))
+            .filter(|target| {
+                Url::parse(target).is_ok_and(|t| matches!(t.scheme(), "http" | "https"))
+            })
+    }
+
+    /// Returns the innermost link, or `None` if the link isn't wrapped
+    #[instrument(skip(self))]
+    pub fn unwrap(&self, url: &str) -> Option<String> {
+        let mut current: Option<String> = None;
+        for _ in 0..self.max_depth {
+            match Self::unwrap_once(current.as_deref().unwrap_or(url)) {
+                Some(target) => {
+                    debug! {%target, "unwrapped url"};
+                    current = Some(target);
+                }
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::init_tests;
+
+    fn create_test_unwrapper() -> LinkUnwrapper {
+        LinkUnwrapper::new(&UnwrapConfig::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unwrap_redirectors() {
+        init_tests().await;
+        let unwrapper = create_test_unwrapper();
+        let cases = [
+            (
+                "https://www.google.com/url?sa=t&source=web&url=https%3A%2F%2Fx.com%2Fuser%2Fstatus%2F1&usg=AOv",
+                "https://x.com/user/status/1",
+            ),
+            (
+                "https://www.google.co.uk/url?q=https://x.com/user/status/1&sa=D",
+                "https://x.com/user/status/1",
+            ),
+            (
+                "https://l.facebook.com/l.php?u=https%3A%2F%2Fwww.tiktok.com%2Ft%2FZTYXjHYeg%2F&h=AT0",
+                "https://www.tiktok.com/t/ZTYXjHYeg/",
+            ),
+            (
+                "https://l.instagram.com/?u=https%3A%2F%2Fexample.com%2Fpage&e=AT1",
+                "https://example.com/page",
+            ),
+            (
+                "https://out.reddit.com/t3_abc123?url=https%3A%2F%2Fexample.com%2Fpage&token=xyz",
+                "https://example.com/page",
+            ),
+            (
+                "https://href.li/?https://example.com/page",
+                "https://example.com/page",
+            ),
+            (
+                "https://www.google.com/amp/s/www.example.com/news/story.amp",
+                "https://www.example.com/news/story.amp",
+            ),
+            (
+                "https://www.google.com/amp/s/site.example/page?id=1",
+                "https://site.example/page?id=1",
+            ),
+            (
+                "https://www.reddit.com/media?url=https%3A%2F%2Fpreview.redd.it%2Fsome-image-v0-8uo8tgdfb08e1.jpeg%3Fwidth%3D640%26s%3Daff0061f",
+                "https://i.redd.it/some-image-v0-8uo8tgdfb08e1.jpeg",
+            ),
+            (
+                "https://www.reddit.com/media?url=https%3A%2F%2Fpreview.redd.it%2Fsome-clip-v0-8uo8tgdfb08e1.mp4%3Fs%3Daff0061f",
+                "https://preview.redd.it/some-clip-v0-8uo8tgdfb08e1.mp4?s=aff0061f",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(unwrapper.unwrap(url).as_deref(), Some(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unwrap_nested_wrappers() {
+        init_tests().await;
+        let unwrapper = create_test_unwrapper();
+        let url = "https://www.google.com/url?q=https%3A%2F%2Fl.facebook.com%2Fl.php%3Fu%3Dhttps%253A%252F%252Fx.com%252Fuser%252Fstatus%252F1";
+
+        assert_eq!(
+            unwrapper.unwrap(url).as_deref(),
+            Some("https://x.com/user/status/1")
+        );
+
+        let shallow_unwrapper = LinkUnwrapper::new(&UnwrapConfig {
+            max_depth: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            shallow_unwrapper.unwrap(url).as_deref(),
+            Some("https://l.facebook.com/l.php?u=https%3A%2F%2Fx.com%2Fuser%2Fstatus%2F1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ignore_unwrapped_and_unsafe_links() {
+        init_tests().await;
+        let unwrapper = create_test_unwrapper();
+        assert!(unwrapper.unwrap("https://x.com/user/status/1").is_none());
+        assert!(unwrapper
+            .unwrap("https://www.google.com/search?q=https://x.com")
+            .is_none());
+        assert!(unwrapper
+            .unwrap("https://www.google.com/url?q=javascript:alert(1)")
+            .is_none());
+        assert!(LinkUnwrapper::new(&UnwrapConfig {
+            enabled: false,
+            ..Default::default()
+        })
+        .is_none());
+    }
+}
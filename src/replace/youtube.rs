@@ -1,6 +1,6 @@
 use super::{
-    LinkReplacer, LinkReplacerConfig, ReplaceConfigError, ReplaceConfigResult, ReplaceError,
-    ReplaceResult,
+    build_regex, BacktrackLimit, LinkReplacer, LinkReplacerConfig, ReplaceConfigError,
+    ReplaceConfigResult, ReplaceError, ReplaceResult,
 };
 use fancy_regex::Regex;
 use tracing::{debug, instrument};
@@ -109,6 +109,14 @@ impl YoutubeConfig {
     }
 }
 
+impl BacktrackLimit for YoutubeConfig {
+    fn backtrack_limit(mut self, limit: usize) -> ReplaceConfigResult<Self> {
+        self.regex = build_regex(self.regex.as_str(), limit)?;
+        self.domain_regex = build_regex(self.domain_regex.as_str(), limit)?;
+        Ok(self)
+    }
+}
+
 impl TryFrom<&LinkReplacerConfig> for YoutubeConfig {
     type Error = ReplaceConfigError;
     fn try_from(value: &LinkReplacerConfig) -> Result<Self, Self::Error> {
@@ -155,7 +163,7 @@ mod test {
         let url = "https://youtube.com/shorts/xFnfOdb35FI/";
         let expected = "https://youtu.be/xFnfOdb35FI/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -167,7 +175,7 @@ mod test {
         let url = "https://www.youtube.com/watch?v=Z5OUviAH2Yc/";
         let expected = "https://youtu.be/Z5OUviAH2Yc/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -179,7 +187,7 @@ mod test {
         let url = "https://www.youtube.com/watch?some=field&v=Z5OUviAH2Yc/";
         let expected = "https://youtu.be/Z5OUviAH2Yc/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
@@ -191,7 +199,7 @@ mod test {
         let url = "https://m.youtube.com/watch?v=Z5OUviAH2Yc/";
         let expected = "https://youtu.be/Z5OUviAH2Yc/";
 
-        let result = test_replacer.transform_url(&url)?;
+        let result = test_replacer.transform_url(url)?;
         assert_eq!(expected, result);
         Ok(())
     }
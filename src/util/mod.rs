@@ -5,12 +5,14 @@ use tracing_subscriber::EnvFilter;
 mod test;
 
 #[cfg(test)]
-pub(crate) use test::{init_tests, spawn_test_server};
+pub(crate) use test::{init_tests, spawn_redirect_server, spawn_test_server};
 
 pub fn get_subscriber(env_filter: String) -> impl Subscriber + Send + Sync {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new(env_filter));
+    // Logs go to stderr so the output of the subcommands can be piped
     tracing_subscriber::fmt()
         .with_env_filter(env_filter)
+        .with_writer(std::io::stderr)
         .finish()
 }
 
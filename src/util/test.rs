@@ -1,8 +1,11 @@
 use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::{get_subscriber, init_subscriber};
 use crate::{start_server, AtomicBotStatus, BotStatus};
-use actix_web::web::Data;
+use actix_web::{web, web::Data, App, HttpResponse, HttpServer};
 use once_cell::sync::Lazy;
 
 static TRACING: Lazy<()> = Lazy::new(|| {
@@ -26,6 +29,74 @@ pub async fn spawn_test_server() -> TestServer {
     let status = Data::new(AtomicBotStatus::new(BotStatus::Starting));
 
     let server = start_server(listener, status.clone()).expect("failed to bind address");
-    let _ = tokio::spawn(server);
+    drop(tokio::spawn(server));
     TestServer { address, status }
 }
+
+pub struct RedirectServer {
+    pub address: String,
+    pub hits: Arc<AtomicUsize>,
+}
+
+/// Stub short-link service for testing the link resolver
+pub async fn spawn_redirect_server() -> RedirectServer {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind random port");
+    let port = listener.local_addr().unwrap().port();
+    let address = format! {"http://127.0.0.1:{}", port};
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let server_hits = Data::from(hits.clone());
+    let flaky_failed = Arc::new(AtomicBool::new(false));
+    let server = HttpServer::new(move || {
+        let flaky_failed = flaky_failed.clone();
+        App::new()
+            .app_data(Data::clone(&server_hits))
+            .route(
+                "/s/tiktok",
+                web::head().to(|hits: Data<AtomicUsize>| async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    HttpResponse::MovedPermanently()
+                        .insert_header(("Location", "https://www.tiktok.com/t/ZTYXjHYeg/"))
+                        .finish()
+                }),
+            )
+            .route(
+                "/hop",
+                web::head().to(|| async {
+                    HttpResponse::Found()
+                        .insert_header(("Location", "/s/tiktok"))
+                        .finish()
+                }),
+            )
+            .route(
+                "/slow",
+                web::head().to(|| async {
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    HttpResponse::Found()
+                        .insert_header(("Location", "https://www.tiktok.com/t/ZTYXjHYeg/"))
+                        .finish()
+                }),
+            )
+            .route(
+                "/flaky",
+                web::head().to(move || {
+                    let failed_before = flaky_failed.swap(true, Ordering::SeqCst);
+                    async move {
+                        if !failed_before {
+                            return HttpResponse::ServiceUnavailable().finish();
+                        }
+                        HttpResponse::Found()
+                            .insert_header(("Location", "https://www.tiktok.com/t/ZTYXjHYeg/"))
+                            .finish()
+                    }
+                }),
+            )
+            .route("/not-a-redirect", web::head().to(HttpResponse::Ok))
+    })
+    .listen(listener)
+    .expect("failed to bind address")
+    .workers(1)
+    .run();
+    drop(tokio::spawn(server));
+    RedirectServer { address, hits }
+}